@@ -1,12 +1,24 @@
 #![no_std]
 
-mod storage;
-mod structures;
+pub mod storage;
+pub mod structures;
 mod helpers;
+mod status_list;
+mod schema_validation;
+mod json_schema;
 
 multiversx_sc::imports!();
 use multiversx_sc::codec::TopDecode;
 use crate::structures::*;
+use crate::json_schema::render_json_schema;
+use crate::status_list::{
+    gzip_compress, multibase_base64url, read_status_bit, write_status_bit, zeroed_bitstring,
+    PendingStatusBits,
+};
+
+// The Bitstring Status List minimum (16 KB), which gives holders group privacy
+const STATUS_LIST_SIZE: u64 = 131_072;
+const MAX_REVOCATION_BATCH: usize = 100;
 
 
 fn decode_credential_id<M: ManagedTypeApi>(buffer: ManagedBuffer<M>) -> u64 {
@@ -21,157 +33,1493 @@ pub trait DigitalIdentityContract:
 {
     #[init]
     fn init(&self) {
-        self.admin().set(&self.blockchain().get_caller());
+        self.admin().set(self.blockchain().get_caller());
         self.set_paused(false);
     }
 
-    // DID Management
-    #[endpoint(createDid)]
-    fn create_did(
+    // DID Management
+    #[endpoint(createDid)]
+    fn create_did(
+        &self,
+        verification_method: VerificationMethod<Self::Api>,
+        services: ManagedVec<Service<Self::Api>>,
+        salt: Option<ManagedBuffer>,
+        next_key_commitment: OptionalValue<ManagedBuffer>,
+    ) {
+        require!(!self.is_paused(), "Contract is paused");
+
+        let caller = self.blockchain().get_caller();
+        let timestamp = self.blockchain().get_block_timestamp();
+
+        // Without a salt, the n-th DID of an address is salted with n (the first one with nothing).
+        // Nonces whose DID was already taken through an explicit salt are skipped.
+        let did = match salt {
+            Some(salt) => {
+                let did = self.generate_did(&caller, &salt);
+                require!(self.is_did_available(&did), "DID already exists");
+                did
+            }
+            None => {
+                let mut nonce = self.did_nonce(&caller).get();
+                loop {
+                    let salt = if nonce == 0 {
+                        ManagedBuffer::new()
+                    } else {
+                        ManagedBuffer::from(&nonce.to_be_bytes())
+                    };
+                    let did = self.generate_did(&caller, &salt);
+                    nonce += 1;
+                    if self.is_did_available(&did) {
+                        self.did_nonce(&caller).set(nonce);
+                        break did;
+                    }
+                }
+            }
+        };
+
+        let mut auth_methods = ManagedVec::new();
+        auth_methods.push(verification_method.id.clone());
+        let assertion_methods = auth_methods.clone();
+
+        let next_key_commitment = next_key_commitment.into_option();
+        if let Some(commitment) = &next_key_commitment {
+            self.require_valid_key_commitment(commitment);
+        }
+
+        let mut document = DidDocument {
+            did: did.clone(),
+            controllers: ManagedVec::from_single_item(caller.clone()),
+            threshold: 1,
+            verification_methods: ManagedVec::from_single_item(verification_method),
+            authentication: auth_methods,
+            assertion_method: assertion_methods,
+            key_agreement: ManagedVec::new(),
+            service_endpoints: ManagedVec::new(),
+            revoked_keys: ManagedVec::new(),
+            next_key_commitment,
+            created: timestamp,
+            updated: timestamp,
+            version_id: 1,
+            deactivated: false,
+        };
+        for service in services.iter() {
+            self.require_fragment_available(&document, &service.id);
+            document.service_endpoints.push(service.clone());
+        }
+
+        self.did_documents(&did).set(&document);
+        self.record_did_version(&document);
+        self.did_by_controller(&caller).insert(did.clone());
+        if self.address_to_did(&caller).is_empty() {
+            self.address_to_did(&caller).set(&did);
+        }
+    }
+
+    #[view(getDidsByController)]
+    fn get_dids_by_controller(&self, controller: ManagedAddress) -> MultiValueEncoded<ManagedBuffer> {
+        self.did_by_controller(&controller).iter().collect()
+    }
+
+    #[endpoint(addVerificationMethod)]
+    fn add_verification_method(
+        &self,
+        did: ManagedBuffer,
+        verification_method: VerificationMethod<Self::Api>,
+    ) {
+        require!(!self.is_paused(), "Contract is paused");
+        let mut document = self.require_direct_did_update(&did);
+        self.apply_did_action(
+            &mut document,
+            DidAction::AddVerificationMethod(verification_method),
+        );
+    }
+
+    #[endpoint(removeVerificationMethod)]
+    fn remove_verification_method(&self, did: ManagedBuffer, method_id: ManagedBuffer) {
+        require!(!self.is_paused(), "Contract is paused");
+        let mut document = self.require_direct_did_update(&did);
+        self.apply_did_action(&mut document, DidAction::RemoveVerificationMethod(method_id));
+    }
+
+    #[endpoint(commitNextKey)]
+    fn commit_next_key(&self, did: ManagedBuffer, commitment: ManagedBuffer) {
+        require!(!self.is_paused(), "Contract is paused");
+        let mut document = self.require_direct_did_update(&did);
+        self.apply_did_action(&mut document, DidAction::CommitNextKey(commitment));
+    }
+
+    // Replaces a key with the one committed to in advance. The SHA-256 hash of
+    // `new_method.public_key_multibase` must match the stored commitment, and
+    // the rotation commits to the key that will replace the new one.
+    #[endpoint(rotateKey)]
+    fn rotate_key(
+        &self,
+        did: ManagedBuffer,
+        method_id: ManagedBuffer,
+        new_method: VerificationMethod<Self::Api>,
+        next_key_commitment: ManagedBuffer,
+    ) {
+        require!(!self.is_paused(), "Contract is paused");
+        let mut document = self.require_direct_did_update(&did);
+        self.apply_did_action(
+            &mut document,
+            DidAction::RotateKey(method_id, new_method, next_key_commitment),
+        );
+    }
+
+    #[endpoint(addService)]
+    fn add_service(&self, did: ManagedBuffer, service: Service<Self::Api>) {
+        require!(!self.is_paused(), "Contract is paused");
+        let mut document = self.require_direct_did_update(&did);
+        self.apply_did_action(&mut document, DidAction::AddService(service));
+    }
+
+    #[endpoint(removeService)]
+    fn remove_service(&self, did: ManagedBuffer, service_id: ManagedBuffer) {
+        require!(!self.is_paused(), "Contract is paused");
+        let mut document = self.require_direct_did_update(&did);
+        self.apply_did_action(&mut document, DidAction::RemoveService(service_id));
+    }
+
+    #[endpoint(setVerificationRelationships)]
+    fn set_verification_relationships(
+        &self,
+        did: ManagedBuffer,
+        authentication: ManagedVec<ManagedBuffer>,
+        assertion_method: ManagedVec<ManagedBuffer>,
+        key_agreement: ManagedVec<ManagedBuffer>,
+    ) {
+        require!(!self.is_paused(), "Contract is paused");
+        let mut document = self.require_direct_did_update(&did);
+        self.apply_did_action(
+            &mut document,
+            DidAction::SetVerificationRelationships(authentication, assertion_method, key_agreement),
+        );
+    }
+
+    #[endpoint(setDidControllers)]
+    fn set_did_controllers_endpoint(
+        &self,
+        did: ManagedBuffer,
+        controllers: ManagedVec<ManagedAddress>,
+        threshold: u32,
+    ) {
+        require!(!self.is_paused(), "Contract is paused");
+        let mut document = self.require_direct_did_update(&did);
+        self.apply_did_action(&mut document, DidAction::SetControllers(controllers, threshold));
+    }
+
+    // Two-step transfer: the controllers name the new controller, who takes
+    // over the DID by accepting it.
+    #[endpoint(transferDidControl)]
+    fn transfer_did_control(&self, did: ManagedBuffer, new_controller: ManagedAddress) {
+        require!(!self.is_paused(), "Contract is paused");
+        let mut document = self.require_direct_did_update(&did);
+        self.apply_did_action(&mut document, DidAction::TransferControl(new_controller));
+    }
+
+    #[endpoint(acceptDidControl)]
+    fn accept_did_control(&self, did: ManagedBuffer) {
+        require!(!self.is_paused(), "Contract is paused");
+        require!(
+            !self.pending_did_transfer(&did).is_empty(),
+            "No pending DID transfer"
+        );
+
+        let caller = self.blockchain().get_caller();
+        require!(
+            self.pending_did_transfer(&did).get() == caller,
+            "Only the new controller can accept the transfer"
+        );
+
+        let mut document = self.did_documents(&did).get();
+        require!(!document.deactivated, "DID is deactivated");
+        self.set_did_controllers(&mut document, ManagedVec::from_single_item(caller.clone()), 1);
+        self.pending_did_transfer(&did).clear();
+
+        // The previous owner's guardians must not be able to take the DID back
+        self.recovery_request(&did).clear();
+        self.recovery_config(&did).clear();
+        self.save_did_document(&mut document);
+
+        self.did_transfer_accepted_event(&did, &caller);
+    }
+
+    #[endpoint(cancelDidTransfer)]
+    fn cancel_did_transfer(&self, did: ManagedBuffer) {
+        self.require_did_controller(&did);
+        require!(
+            !self.pending_did_transfer(&did).is_empty(),
+            "No pending DID transfer"
+        );
+
+        self.pending_did_transfer(&did).clear();
+
+        self.did_transfer_cancelled_event(&did);
+    }
+
+    // Multi-controller DIDs: any controller proposes an update, which runs as
+    // soon as the approvals of current controllers reach the DID threshold.
+    #[endpoint(proposeDidUpdate)]
+    fn propose_did_update(&self, did: ManagedBuffer, action: DidAction<Self::Api>) -> u64 {
+        require!(!self.is_paused(), "Contract is paused");
+        self.require_did_controller(&did);
+        let caller = self.blockchain().get_caller();
+
+        let proposal_id = self.next_did_proposal_id().get();
+        let mut proposal = DidProposal {
+            id: proposal_id,
+            did: did.clone(),
+            proposer: caller.clone(),
+            action,
+            approvals: ManagedVec::from_single_item(caller.clone()),
+            created: self.blockchain().get_block_timestamp(),
+            executed: false,
+        };
+        self.next_did_proposal_id().set(proposal_id + 1);
+
+        self.did_update_proposed_event(&did, proposal_id, &caller);
+        self.try_execute_did_proposal(&mut proposal);
+        self.did_proposals(&proposal_id).set(&proposal);
+
+        proposal_id
+    }
+
+    #[endpoint(approveDidUpdate)]
+    fn approve_did_update(&self, proposal_id: u64) {
+        require!(!self.is_paused(), "Contract is paused");
+        require!(
+            !self.did_proposals(&proposal_id).is_empty(),
+            "Proposal not found"
+        );
+
+        let mut proposal = self.did_proposals(&proposal_id).get();
+        require!(!proposal.executed, "Proposal already executed");
+        self.require_did_controller(&proposal.did);
+
+        let caller = self.blockchain().get_caller();
+        require!(!proposal.approvals.contains(&caller), "Proposal already approved");
+        proposal.approvals.push(caller.clone());
+
+        self.did_update_approved_event(&proposal.did, proposal_id, &caller);
+        self.try_execute_did_proposal(&mut proposal);
+        self.did_proposals(&proposal_id).set(&proposal);
+    }
+
+    fn try_execute_did_proposal(&self, proposal: &mut DidProposal<Self::Api>) {
+        let mut document = self.did_documents(&proposal.did).get();
+        if self.count_controller_approvals(&document, &proposal.approvals) < document.threshold {
+            return;
+        }
+
+        proposal.executed = true;
+        self.apply_did_action(&mut document, proposal.action.clone());
+        self.did_update_executed_event(&proposal.did, proposal.id);
+    }
+
+    // Applies an authorised update to the document and records the new version.
+    // Transfers and guardian changes are stored outside the document, so they
+    // leave its version untouched.
+    fn apply_did_action(&self, document: &mut DidDocument<Self::Api>, action: DidAction<Self::Api>) {
+        let did = document.did.clone();
+        let changes_document = !matches!(
+            action,
+            DidAction::TransferControl(_) | DidAction::SetRecoveryGuardians(..)
+        );
+        match action {
+            DidAction::AddVerificationMethod(verification_method) => {
+                self.require_fragment_available(document, &verification_method.id);
+
+                let method_id = verification_method.id.clone();
+                document.verification_methods.push(verification_method);
+                self.verification_method_added_event(&did, &method_id);
+            }
+            DidAction::RemoveVerificationMethod(method_id) => {
+                let index = self.verification_method_index(document, &method_id);
+                require!(index.is_some(), "Verification method not found");
+                self.retire_verification_method(document, index.unwrap());
+
+                document.authentication = self.without_fragment(&document.authentication, &method_id);
+                document.assertion_method = self.without_fragment(&document.assertion_method, &method_id);
+                document.key_agreement = self.without_fragment(&document.key_agreement, &method_id);
+                require!(
+                    !document.authentication.is_empty(),
+                    "Cannot remove the last authentication method"
+                );
+                self.verification_method_removed_event(&did, &method_id);
+            }
+            DidAction::CommitNextKey(commitment) => {
+                require!(
+                    document.next_key_commitment.is_none(),
+                    "Next key commitment already set"
+                );
+                self.require_valid_key_commitment(&commitment);
+
+                document.next_key_commitment = Some(commitment.clone());
+                self.next_key_committed_event(&did, &commitment);
+            }
+            DidAction::RotateKey(method_id, new_method, next_key_commitment) => {
+                let commitment = match &document.next_key_commitment {
+                    Some(commitment) => commitment.clone(),
+                    None => sc_panic!("No next key commitment"),
+                };
+                let new_key_hash = self.crypto().sha256(&new_method.public_key_multibase);
+                require!(
+                    *new_key_hash.as_managed_buffer() == commitment,
+                    "New key does not match the pre-rotation commitment"
+                );
+                self.require_valid_key_commitment(&next_key_commitment);
+
+                let index = self.verification_method_index(document, &method_id);
+                require!(index.is_some(), "Verification method not found");
+                self.require_fragment_available(document, &new_method.id);
+
+                self.retire_verification_method(document, index.unwrap());
+                document.authentication =
+                    self.replace_fragment(&document.authentication, &method_id, &new_method.id);
+                document.assertion_method =
+                    self.replace_fragment(&document.assertion_method, &method_id, &new_method.id);
+                document.key_agreement =
+                    self.replace_fragment(&document.key_agreement, &method_id, &new_method.id);
+
+                let new_method_id = new_method.id.clone();
+                document.verification_methods.push(new_method);
+                document.next_key_commitment = Some(next_key_commitment);
+                self.key_rotated_event(&did, &method_id, &new_method_id);
+            }
+            DidAction::AddService(service) => {
+                self.require_fragment_available(document, &service.id);
+
+                let service_id = service.id.clone();
+                document.service_endpoints.push(service);
+                self.service_added_event(&did, &service_id);
+            }
+            DidAction::RemoveService(service_id) => {
+                let index = self.service_index(document, &service_id);
+                require!(index.is_some(), "Service not found");
+                document.service_endpoints.remove(index.unwrap());
+                self.service_removed_event(&did, &service_id);
+            }
+            DidAction::SetVerificationRelationships(authentication, assertion_method, key_agreement) => {
+                require!(
+                    !authentication.is_empty(),
+                    "At least one authentication method is required"
+                );
+                self.require_known_methods(document, &authentication);
+                self.require_known_methods(document, &assertion_method);
+                self.require_known_methods(document, &key_agreement);
+                if document.next_key_commitment.is_some() {
+                    self.require_no_uncommitted_keys(&document.authentication, &authentication);
+                    self.require_no_uncommitted_keys(&document.assertion_method, &assertion_method);
+                }
+
+                document.authentication = authentication;
+                document.assertion_method = assertion_method;
+                document.key_agreement = key_agreement;
+                self.verification_relationships_updated_event(&did);
+            }
+            DidAction::SetControllers(controllers, threshold) => {
+                self.set_did_controllers(document, controllers, threshold);
+                self.did_controllers_updated_event(&did, threshold);
+            }
+            DidAction::SetRecoveryGuardians(guardian_addresses, guardian_dids, quorum, delay) => {
+                let config = RecoveryConfig {
+                    guardian_addresses,
+                    guardian_dids,
+                    quorum,
+                    delay,
+                };
+                self.set_recovery_config(document, config);
+                self.recovery_guardians_updated_event(&did, quorum);
+            }
+            DidAction::TransferControl(new_controller) => {
+                require!(
+                    !document.controllers.contains(&new_controller),
+                    "Address already controls the DID"
+                );
+
+                self.pending_did_transfer(&did).set(&new_controller);
+                self.did_transfer_started_event(&did, &new_controller);
+            }
+            DidAction::Deactivate(revoke_credentials, revoke_delegations) => {
+                document.deactivated = true;
+
+                if !self.issuer_by_did(&did).is_empty() {
+                    let issuer = self.issuer_by_did(&did).get();
+                    if revoke_credentials {
+                        self.credential_revocation_cursor(&issuer).set(1);
+                        self.revoke_issued_credentials(&issuer);
+                    }
+                    if revoke_delegations {
+                        self.delegation_revocation_cursor(&issuer).set(1);
+                        self.revoke_granted_delegations(&issuer);
+                    }
+                }
+                self.did_deactivated_event(&did);
+            }
+        }
+
+        if changes_document {
+            self.save_did_document(document);
+        }
+    }
+
+    // Issuer Registry
+    #[endpoint(registerIssuer)]
+    fn register_issuer(
+        &self,
+        address: ManagedAddress,
+        name: ManagedBuffer,
+        did: ManagedBuffer,
+        website: ManagedBuffer,
+        accreditation: ManagedBuffer,
+    ) {
+        self.require_admin();
+        require!(self.issuers(&address).is_empty(), "Issuer already registered");
+        require!(!self.did_documents(&did).is_empty(), "Issuer DID not found");
+        let document = self.did_documents(&did).get();
+        require!(document.controllers.contains(&address), "Issuer must control the DID");
+        require!(!document.deactivated, "Issuer DID is deactivated");
+        require!(
+            self.issuer_by_did(&did).is_empty(),
+            "DID is already linked to an issuer"
+        );
+
+        let timestamp = self.blockchain().get_block_timestamp();
+        let profile = IssuerProfile {
+            address: address.clone(),
+            name,
+            did: did.clone(),
+            website,
+            accreditation,
+            status: IssuerStatus::Active,
+            registered: timestamp,
+            updated: timestamp,
+        };
+
+        self.issuers(&address).set(&profile);
+        self.issuer_addresses().insert(address.clone());
+        self.issuer_by_did(&did).set(&address);
+
+        self.issuer_registered_event(&address, &did);
+    }
+
+    #[endpoint(suspendIssuer)]
+    fn suspend_issuer(&self, address: ManagedAddress) {
+        self.require_admin();
+        require!(!self.issuers(&address).is_empty(), "Issuer not found");
+
+        let mut profile = self.issuers(&address).get();
+        require!(
+            profile.status == IssuerStatus::Active,
+            "Issuer is already suspended"
+        );
+
+        profile.status = IssuerStatus::Suspended;
+        profile.updated = self.blockchain().get_block_timestamp();
+        self.issuers(&address).set(&profile);
+
+        self.issuer_suspended_event(&address);
+    }
+
+    #[endpoint(reinstateIssuer)]
+    fn reinstate_issuer(&self, address: ManagedAddress) {
+        self.require_admin();
+        require!(!self.issuers(&address).is_empty(), "Issuer not found");
+
+        let mut profile = self.issuers(&address).get();
+        require!(
+            profile.status == IssuerStatus::Suspended,
+            "Issuer is not suspended"
+        );
+
+        profile.status = IssuerStatus::Active;
+        profile.updated = self.blockchain().get_block_timestamp();
+        self.issuers(&address).set(&profile);
+
+        self.issuer_reinstated_event(&address);
+    }
+
+    #[endpoint(removeIssuer)]
+    fn remove_issuer(&self, address: ManagedAddress) {
+        self.require_admin();
+        require!(!self.issuers(&address).is_empty(), "Issuer not found");
+
+        let profile = self.issuers(&address).get();
+        self.issuer_by_did(&profile.did).clear();
+        self.issuer_addresses().swap_remove(&address);
+        self.issuers(&address).clear();
+
+        self.issuer_removed_event(&address);
+    }
+
+    // Pages follow the storage order of the issuer set, which is not stable:
+    // removing an issuer moves the last listed one into its slot, so pages
+    // read across a removal can skip or repeat that issuer.
+    #[view(getIssuers)]
+    fn get_issuers(&self, from: usize, size: usize) -> MultiValueEncoded<IssuerProfile<Self::Api>> {
+        let mut result = MultiValueEncoded::new();
+        for address in self.issuer_addresses().iter().skip(from).take(size) {
+            result.push(self.issuers(&address).get());
+        }
+        result
+    }
+
+    #[view(getIssuerCount)]
+    fn get_issuer_count(&self) -> usize {
+        self.issuer_addresses().len()
+    }
+
+    #[endpoint(setRecoveryGuardians)]
+    fn set_recovery_guardians(
+        &self,
+        did: ManagedBuffer,
+        guardian_addresses: ManagedVec<ManagedAddress>,
+        guardian_dids: ManagedVec<ManagedBuffer>,
+        quorum: u32,
+        delay: u64,
+    ) {
+        require!(!self.is_paused(), "Contract is paused");
+        let mut document = self.require_direct_did_update(&did);
+        self.apply_did_action(
+            &mut document,
+            DidAction::SetRecoveryGuardians(guardian_addresses, guardian_dids, quorum, delay),
+        );
+    }
+
+    // Social recovery: guardians approve moving control to a new address. Once
+    // the quorum is reached, the recovery can be executed after the configured
+    // delay unless a current controller cancels it first.
+    #[endpoint(initiateRecovery)]
+    fn initiate_recovery(&self, did: ManagedBuffer, new_controller: ManagedAddress) {
+        require!(!self.is_paused(), "Contract is paused");
+        require!(!self.did_documents(&did).is_empty(), "DID not found");
+        require!(!self.did_documents(&did).get().deactivated, "DID is deactivated");
+        require!(
+            !self.recovery_config(&did).is_empty(),
+            "No recovery guardians configured"
+        );
+        require!(
+            self.recovery_request(&did).is_empty(),
+            "Recovery already in progress"
+        );
+
+        let caller = self.blockchain().get_caller();
+        let config = self.recovery_config(&did).get();
+        let mut request = RecoveryRequest {
+            new_controller: new_controller.clone(),
+            initiator: caller.clone(),
+            approved_addresses: ManagedVec::new(),
+            approved_dids: ManagedVec::new(),
+            guardian_did_approvers: ManagedVec::new(),
+            initiated: self.blockchain().get_block_timestamp(),
+            quorum_reached: None,
+        };
+        self.record_guardian_approval(&config, &mut request, &caller);
+        self.recovery_request(&did).set(&request);
+
+        self.recovery_initiated_event(&did, &new_controller, &caller);
+    }
+
+    #[endpoint(approveRecovery)]
+    fn approve_recovery(&self, did: ManagedBuffer) {
+        require!(!self.is_paused(), "Contract is paused");
+        require!(
+            !self.recovery_request(&did).is_empty(),
+            "No recovery in progress"
+        );
+
+        let caller = self.blockchain().get_caller();
+        let config = self.recovery_config(&did).get();
+        let mut request = self.recovery_request(&did).get();
+        require!(
+            self.record_guardian_approval(&config, &mut request, &caller),
+            "Recovery already approved"
+        );
+        self.recovery_request(&did).set(&request);
+
+        self.recovery_approved_event(&did, &caller);
+    }
+
+    #[endpoint(cancelRecovery)]
+    fn cancel_recovery(&self, did: ManagedBuffer) {
+        self.require_did_controller(&did);
+        require!(
+            !self.recovery_request(&did).is_empty(),
+            "No recovery in progress"
+        );
+
+        self.recovery_request(&did).clear();
+
+        self.recovery_cancelled_event(&did, &self.blockchain().get_caller());
+    }
+
+    #[endpoint(executeRecovery)]
+    fn execute_recovery(&self, did: ManagedBuffer) {
+        require!(!self.is_paused(), "Contract is paused");
+        require!(
+            !self.recovery_request(&did).is_empty(),
+            "No recovery in progress"
+        );
+
+        let config = self.recovery_config(&did).get();
+        let request = self.recovery_request(&did).get();
+        let quorum_reached = match request.quorum_reached {
+            Some(timestamp) => timestamp,
+            None => sc_panic!("Recovery quorum not reached"),
+        };
+        require!(
+            self.blockchain().get_block_timestamp() >= quorum_reached + config.delay,
+            "Recovery waiting period has not ended"
+        );
+
+        let mut document = self.did_documents(&did).get();
+        require!(!document.deactivated, "DID is deactivated");
+        self.set_did_controllers(
+            &mut document,
+            ManagedVec::from_single_item(request.new_controller.clone()),
+            1,
+        );
+        self.recovery_request(&did).clear();
+        self.save_did_document(&mut document);
+
+        self.recovery_executed_event(&did, &request.new_controller);
+    }
+
+    #[view(resolveDid)]
+    fn resolve_did(&self, did_url: ManagedBuffer) -> DidResolutionResult<Self::Api> {
+        self.resolve_did_url(&did_url)
+    }
+
+    #[view(getDidDocumentVersion)]
+    fn get_did_document_version(
+        &self,
+        did: ManagedBuffer,
+        version_id: usize,
+    ) -> DidDocumentVersion<Self::Api> {
+        let versions = self.did_document_versions(&did);
+        require!(
+            version_id >= 1 && version_id <= versions.len(),
+            "DID document version not found"
+        );
+        versions.get(version_id)
+    }
+
+    #[view(getDidDocumentVersionCount)]
+    fn get_did_document_version_count(&self, did: ManagedBuffer) -> usize {
+        self.did_document_versions(&did).len()
+    }
+
+    // Deactivation is permanent. The issuer linked to the DID can optionally have
+    // every credential it issued and every delegation it granted revoked with it.
+    // Each cascade handles `MAX_REVOCATION_BATCH` entries per transaction and is
+    // resumed with `continueCredentialRevocation` / `continueDelegationRevocation`.
+    #[endpoint(deactivateDid)]
+    fn deactivate_did(
+        &self,
+        did: ManagedBuffer,
+        revoke_credentials: bool,
+        revoke_delegations: bool,
+    ) {
+        require!(!self.is_paused(), "Contract is paused");
+        let mut document = self.require_direct_did_update(&did);
+        self.apply_did_action(
+            &mut document,
+            DidAction::Deactivate(revoke_credentials, revoke_delegations),
+        );
+    }
+
+    // Continues revoking the credentials of a deactivated issuer when the
+    // cascade did not fit in a single transaction
+    #[endpoint(continueCredentialRevocation)]
+    fn continue_credential_revocation(&self, did: ManagedBuffer) {
+        require!(!self.is_paused(), "Contract is paused");
+        require!(!self.issuer_by_did(&did).is_empty(), "No issuer registered for DID");
+        let issuer = self.issuer_by_did(&did).get();
+        require!(
+            !self.credential_revocation_cursor(&issuer).is_empty(),
+            "No credential revocation in progress"
+        );
+        self.revoke_issued_credentials(&issuer);
+    }
+
+    // Revokes the next batch of credentials from the issuer's cursor, clearing
+    // the cursor once every credential has been processed
+    fn revoke_issued_credentials(&self, issuer: &ManagedAddress) {
+        let issued = self.issuer_credentials(issuer);
+        let start = self.credential_revocation_cursor(issuer).get();
+        let end = issued.len().min(start + MAX_REVOCATION_BATCH - 1);
+        let reason = ManagedBuffer::from("Issuer DID deactivated");
+
+        let mut pending = PendingStatusBits::new();
+        for index in start..=end {
+            let credential_id = issued.get(index);
+            let mut credential = self.credentials(&credential_id).get();
+            if credential.revoked {
+                continue;
+            }
+
+            self.record_credential_revocation(credential_id, &mut credential, issuer, &reason);
+            self.stage_credential_status_bit(&mut pending, &credential, StatusPurpose::Revocation, true);
+            self.credential_revoked_event(credential_id, issuer, &reason);
+        }
+        self.store_status_bits(&pending);
+
+        if end >= issued.len() {
+            self.credential_revocation_cursor(issuer).clear();
+        } else {
+            self.credential_revocation_cursor(issuer).set(end + 1);
+        }
+    }
+
+    // Same as `continueCredentialRevocation`, for the delegations the issuer granted
+    #[endpoint(continueDelegationRevocation)]
+    fn continue_delegation_revocation(&self, did: ManagedBuffer) {
+        require!(!self.is_paused(), "Contract is paused");
+        require!(!self.issuer_by_did(&did).is_empty(), "No issuer registered for DID");
+        let issuer = self.issuer_by_did(&did).get();
+        require!(
+            !self.delegation_revocation_cursor(&issuer).is_empty(),
+            "No delegation revocation in progress"
+        );
+        self.revoke_granted_delegations(&issuer);
+    }
+
+    fn revoke_granted_delegations(&self, delegator: &ManagedAddress) {
+        let granted = self.delegator_delegations(delegator);
+        let start = self.delegation_revocation_cursor(delegator).get();
+        let end = granted.len().min(start + MAX_REVOCATION_BATCH - 1);
+
+        for index in start..=end {
+            let delegation_id = granted.get(index);
+            let mut delegation = self.delegations(&delegation_id).get();
+            if delegation.revoked {
+                continue;
+            }
+
+            delegation.revoked = true;
+            self.delegations(&delegation_id).set(&delegation);
+            self.delegation_revoked_event(delegation_id, delegator);
+        }
+
+        if end >= granted.len() {
+            self.delegation_revocation_cursor(delegator).clear();
+        } else {
+            self.delegation_revocation_cursor(delegator).set(end + 1);
+        }
+    }
+
+    // Schema Management
+    #[endpoint(registerSchema)]
+    fn register_schema(
+        &self,
+        type_: ManagedBuffer,
+        attributes: ManagedVec<SchemaAttribute<Self::Api>>,
+        version: ManagedBuffer,
+        strict: bool,
+        additional_properties: bool,
+    ) {
+        require!(!self.is_paused(), "Contract is paused");
+        let caller = self.blockchain().get_caller();
+        require!(
+            self.is_active_issuer(&caller),
+            "Only registered issuers can create schemas"
+        );
+        self.validate_schema_attributes(&attributes);
+
+        let issuer_did = self.issuers(&caller).get().did;
+        let schema_id = self.generate_schema_id(&issuer_did, &type_, &version);
+        require!(
+            self.credential_schemas(&schema_id).is_empty(),
+            "Schema already exists"
+        );
+
+        self.schema_ids().push(&schema_id);
+        self.issuer_schemas(&caller).push(&schema_id);
+        self.schemas_by_type(&type_).push(&schema_id);
+        self.schema_versions(&issuer_did, &type_).push(&schema_id);
+
+        let schema = CredentialSchema {
+            id: schema_id.clone(),
+            type_,
+            attributes,
+            issuer: caller,
+            issuer_did,
+            version,
+            strict,
+            additional_properties,
+            created: self.blockchain().get_block_timestamp(),
+            deprecated_at: None,
+            superseded_by: None,
+        };
+
+        self.credential_schemas(&schema_id).set(&schema);
+        self.schema_registered_event(&schema_id, &schema.issuer, &schema.type_, &schema.version);
+    }
+
+    #[endpoint(deprecateSchema)]
+    fn deprecate_schema(&self, schema_id: ManagedBuffer, superseded_by: OptionalValue<ManagedBuffer>) {
+        require!(!self.is_paused(), "Contract is paused");
+        require!(
+            !self.credential_schemas(&schema_id).is_empty(),
+            "Schema not found"
+        );
+        let mut schema = self.credential_schemas(&schema_id).get();
+        require!(
+            schema.issuer == self.blockchain().get_caller(),
+            "Only the schema issuer can deprecate it"
+        );
+        require!(schema.deprecated_at.is_none(), "Schema already deprecated");
+
+        let superseded_by = superseded_by.into_option();
+        if let Some(successor_id) = &superseded_by {
+            require!(*successor_id != schema_id, "Schema cannot supersede itself");
+            require!(
+                !self.credential_schemas(successor_id).is_empty(),
+                "Superseding schema not found"
+            );
+            let successor = self.credential_schemas(successor_id).get();
+            require!(
+                successor.issuer_did == schema.issuer_did && successor.type_ == schema.type_,
+                "Superseding schema must be a version of the same type from the same issuer"
+            );
+            require!(
+                successor.deprecated_at.is_none(),
+                "Superseding schema is deprecated"
+            );
+        }
+
+        schema.deprecated_at = Some(self.blockchain().get_block_timestamp());
+        schema.superseded_by = superseded_by;
+        self.credential_schemas(&schema_id).set(&schema);
+
+        self.schema_deprecated_event(&schema_id, &schema.issuer);
+    }
+
+    #[endpoint(setSchemaDeprecationGracePeriod)]
+    fn set_schema_deprecation_grace_period(&self, grace_period: u64) {
+        self.require_admin();
+        self.schema_deprecation_grace_period().set(grace_period);
+    }
+
+    #[view(getSchema)]
+    fn get_schema(&self, schema_id: ManagedBuffer) -> CredentialSchema<Self::Api> {
+        require!(
+            !self.credential_schemas(&schema_id).is_empty(),
+            "Schema not found"
+        );
+        self.credential_schemas(&schema_id).get()
+    }
+
+    #[view(getJsonSchema)]
+    fn get_json_schema(&self, schema_id: ManagedBuffer) -> ManagedBuffer {
+        require!(
+            !self.credential_schemas(&schema_id).is_empty(),
+            "Schema not found"
+        );
+        render_json_schema(&self.credential_schemas(&schema_id).get())
+    }
+
+    #[view(getSchemas)]
+    fn get_schemas(&self, from: usize, size: usize) -> MultiValueEncoded<CredentialSchema<Self::Api>> {
+        let mut result = MultiValueEncoded::new();
+        for schema_id in self.schema_ids().iter().skip(from).take(size) {
+            result.push(self.credential_schemas(&schema_id).get());
+        }
+        result
+    }
+
+    #[view(getSchemaCount)]
+    fn get_schema_count(&self) -> usize {
+        self.schema_ids().len()
+    }
+
+    #[view(getSchemasByIssuer)]
+    fn get_schemas_by_issuer(
+        &self,
+        issuer: ManagedAddress,
+        from: usize,
+        size: usize,
+    ) -> MultiValueEncoded<CredentialSchema<Self::Api>> {
+        let mut result = MultiValueEncoded::new();
+        for schema_id in self.issuer_schemas(&issuer).iter().skip(from).take(size) {
+            result.push(self.credential_schemas(&schema_id).get());
+        }
+        result
+    }
+
+    #[view(getSchemasByType)]
+    fn get_schemas_by_type(
+        &self,
+        type_: ManagedBuffer,
+        from: usize,
+        size: usize,
+    ) -> MultiValueEncoded<CredentialSchema<Self::Api>> {
+        let mut result = MultiValueEncoded::new();
+        for schema_id in self.schemas_by_type(&type_).iter().skip(from).take(size) {
+            result.push(self.credential_schemas(&schema_id).get());
+        }
+        result
+    }
+
+    #[view(getSchemaVersions)]
+    fn get_schema_versions(
+        &self,
+        issuer_did: ManagedBuffer,
+        type_: ManagedBuffer,
+    ) -> MultiValueEncoded<CredentialSchema<Self::Api>> {
+        let mut result = MultiValueEncoded::new();
+        for schema_id in self.schema_versions(&issuer_did, &type_).iter() {
+            result.push(self.credential_schemas(&schema_id).get());
+        }
+        result
+    }
+
+    // Credential Management
+    #[endpoint(issueCredential)]
+    fn issue_credential(
+        &self,
+        holder: ManagedAddress,
+        credential_type: ManagedVec<ManagedBuffer>,
+        schema_id: ManagedBuffer,
+        claims: Claims<Self::Api>,
+        expiration_date: Option<u64>,
+        encryption_key: ManagedBuffer,
+        verification_method: ManagedBuffer,
+        signature: ManagedBuffer,
+        delegation_id: Option<u64>,
+    ) {
+        require!(!self.is_paused(), "Contract is paused");
+        let caller = self.blockchain().get_caller();
+
+        // Under a delegation the delegator stays the accountable issuer
+        let issuer = match delegation_id {
+            Some(delegation_id) => {
+                require!(
+                    !self.delegations(&delegation_id).is_empty(),
+                    "Delegation not found"
+                );
+                require!(
+                    self.delegations(&delegation_id).get().delegate == caller,
+                    "Only the delegate can issue under this delegation"
+                );
+                self.verify_delegation(delegation_id, &credential_type);
+
+                let chain = self.delegation_chain(delegation_id);
+                let root_delegator = self.delegations(&chain.get(0)).get().delegator;
+                require!(
+                    self.is_active_issuer(&root_delegator),
+                    "Delegating issuer is not active"
+                );
+
+                // Usage limits apply at every level of the chain
+                for id in chain.iter() {
+                    let mut delegation = self.delegations(&id).get();
+                    self.require_within_delegation_limits(&delegation, &schema_id, &holder);
+                    delegation.issued_count += 1;
+                    self.delegations(&id).set(&delegation);
+                }
+                root_delegator
+            }
+            None => {
+                require!(
+                    self.is_active_issuer(&caller),
+                    "Only registered issuers can issue credentials"
+                );
+                caller.clone()
+            }
+        };
+
+        // Validate schema
+        require!(
+            !self.credential_schemas(&schema_id).is_empty(),
+            "Schema not found"
+        );
+        let schema = self.credential_schemas(&schema_id).get();
+        self.require_schema_usable(&schema);
+        self.validate_claims_against_schema(&claims, &schema);
+        let mut claims = claims;
+        self.seal_claims(&mut claims, &schema);
+
+        let timestamp = self.blockchain().get_block_timestamp();
+        let credential_id = self.generate_credential_id();
+
+        let signer_did = self.signing_did(&issuer, &caller, &verification_method);
+        let message = self.credential_signing_message(
+            &issuer,
+            &holder,
+            &credential_type,
+            &schema_id,
+            &claims.merkle_root,
+            expiration_date,
+            delegation_id,
+        );
+        let proof = self.build_credential_proof(
+            &signer_did,
+            &verification_method,
+            &message,
+            signature,
+            timestamp,
+        );
+
+        let credential_id_u64 = decode_credential_id(credential_id.clone());
+        let credential = Credential {
+            id: self.managed_buffer_from_bytes(credential_id.to_boxed_bytes().as_ref()),
+            issuer: issuer.clone(),
+            holder: holder.clone(),
+            credential_type,
+            schema_id,
+            content_hash: claims.merkle_root.clone(),
+            claims,
+            issuance_date: timestamp,
+            expiration_date,
+            revoked: false,
+            suspension: None,
+            status: self.assign_credential_status(&issuer, credential_id_u64),
+            delegation_id,
+            encryption_key,
+            proof,
+        };
+
+        self.credentials(&credential_id_u64).set(&credential);
+        self.holder_credentials(&holder).push(&credential_id_u64);
+        self.issuer_credentials(&issuer).push(&credential_id_u64);
+    }
+
+    #[endpoint(revokeCredential)]
+    fn revoke_credential_endpoint(&self, credential_id: u64, reason: ManagedBuffer) {
+        require!(!self.is_paused(), "Contract is paused");
+        require!(
+            !self.credentials(&credential_id).is_empty(),
+            "Credential not found"
+        );
+
+        let caller = self.blockchain().get_caller();
+        let mut credential = self.credentials(&credential_id).get();
+        require!(
+            self.can_manage_credential(&caller, &credential),
+            "Only the issuer or a valid delegate can revoke the credential"
+        );
+        require!(!credential.revoked, "Credential is already revoked");
+
+        self.revoke_credential(credential_id, &mut credential, &caller, &reason);
+
+        self.credential_revoked_event(credential_id, &caller, &reason);
+    }
+
+    #[endpoint(suspendCredential)]
+    fn suspend_credential(
+        &self,
+        credential_id: u64,
+        reason: ManagedBuffer,
+        suspended_until: OptionalValue<u64>,
+    ) {
+        require!(!self.is_paused(), "Contract is paused");
+        require!(
+            !self.credentials(&credential_id).is_empty(),
+            "Credential not found"
+        );
+
+        let caller = self.blockchain().get_caller();
+        let mut credential = self.credentials(&credential_id).get();
+        require!(
+            self.can_manage_credential(&caller, &credential),
+            "Only the issuer or a valid delegate can suspend the credential"
+        );
+        require!(!credential.revoked, "Credential is revoked");
+        require!(!self.is_suspended(&credential), "Credential is already suspended");
+
+        let timestamp = self.blockchain().get_block_timestamp();
+        let suspended_until = suspended_until.into_option();
+        if let Some(until) = suspended_until {
+            require!(until > timestamp, "Suspension must end in the future");
+            // A status list bit cannot lapse by itself, so listed credentials are
+            // suspended until explicitly reinstated
+            require!(
+                !credential
+                    .status
+                    .iter()
+                    .any(|entry| entry.purpose == StatusPurpose::Suspension),
+                "Timed suspension is not available for credentials on a suspension list"
+            );
+        }
+
+        credential.suspension = Some(CredentialSuspension {
+            reason: reason.clone(),
+            suspended_at: timestamp,
+            suspended_until,
+            suspended_by: caller.clone(),
+        });
+        self.credentials(&credential_id).set(&credential);
+        self.set_credential_status_bit(&credential, StatusPurpose::Suspension, true);
+
+        self.credential_suspended_event(credential_id, &caller, &reason);
+    }
+
+    #[endpoint(reinstateCredential)]
+    fn reinstate_credential(&self, credential_id: u64) {
+        require!(!self.is_paused(), "Contract is paused");
+        require!(
+            !self.credentials(&credential_id).is_empty(),
+            "Credential not found"
+        );
+
+        let caller = self.blockchain().get_caller();
+        let mut credential = self.credentials(&credential_id).get();
+        require!(
+            self.can_manage_credential(&caller, &credential),
+            "Only the issuer or a valid delegate can reinstate the credential"
+        );
+        require!(credential.suspension.is_some(), "Credential is not suspended");
+
+        credential.suspension = None;
+        self.credentials(&credential_id).set(&credential);
+        self.set_credential_status_bit(&credential, StatusPurpose::Suspension, false);
+
+        self.credential_reinstated_event(credential_id, &caller);
+    }
+
+    // Lets a holder prove one claim of a credential without revealing the others.
+    // Private claims are not part of the Merkle tree and cannot be proven.
+    #[view(verifyClaimInclusion)]
+    fn verify_claim_inclusion(
         &self,
-        verification_method: VerificationMethod<Self::Api>,
-        services: ManagedVec<Service<Self::Api>>,
-    ) {
-        require!(!self.is_paused(), "Contract is paused");
-
-        let caller = self.blockchain().get_caller();
-        let timestamp = self.blockchain().get_block_timestamp();
+        credential_id: u64,
+        attribute: ManagedBuffer,
+        value: ManagedBuffer,
+        salt: ManagedBuffer,
+        proof_path: MultiValueEncoded<ManagedBuffer>,
+    ) -> bool {
+        require!(
+            !self.credentials(&credential_id).is_empty(),
+            "Credential not found"
+        );
+        let credential = self.credentials(&credential_id).get();
+        let schema = self.credential_schemas(&credential.schema_id).get();
+        require!(
+            self.is_revealable_claim(&schema, &attribute),
+            "Private claims cannot be disclosed"
+        );
+        let leaf = self.claim_leaf_hash(&attribute, &value, &salt);
+        self.verify_merkle_proof(leaf, proof_path.into_iter(), &credential.claims.merkle_root)
+    }
 
-        let did = self.generate_did(&caller);
-        require!(self.did_documents(&did).is_empty(), "DID already exists");
+    // Ed25519 and BLS signature checks abort the VM on failure, so for those
+    // key types an invalid proof fails the query instead of returning
+    // `proof_valid = false`. Only secp256k1 proofs report `false`.
+    #[view(verifyCredential)]
+    fn verify_credential_view(&self, credential_id: u64) -> VerificationResult<Self::Api> {
+        require!(
+            !self.credentials(&credential_id).is_empty(),
+            "Credential not found"
+        );
 
-        let mut auth_methods = ManagedVec::new();
-        auth_methods.push(verification_method.id.clone());
+        let credential = self.credentials(&credential_id).get();
+        self.verify_credential(credential_id, &credential)
+    }
 
-        let document = DidDocument {
-            did: did.clone(),
-            controller: caller.clone(),
-            verification_methods: ManagedVec::from_single_item(verification_method),
-            authentication: auth_methods,
-            assertion_method: ManagedVec::new(),
-            key_agreement: ManagedVec::new(),
-            service_endpoints: services,
+    // Status Lists
+    // A newly allocated list becomes the issuer's current list for its purpose;
+    // credentials issued afterwards are assigned the next free index in it.
+    // Every list holds `STATUS_LIST_SIZE` bits.
+    #[endpoint(allocateStatusList)]
+    fn allocate_status_list(&self, purpose: StatusPurpose) -> u64 {
+        require!(!self.is_paused(), "Contract is paused");
+        let caller = self.blockchain().get_caller();
+        require!(
+            self.is_active_issuer(&caller),
+            "Only registered issuers can allocate status lists"
+        );
+        let timestamp = self.blockchain().get_block_timestamp();
+        // Ids start at 1: an issuer's current list id of 0 would read as unset
+        let list_id = self.next_status_list_id().get().max(1);
+        let list = StatusList {
+            id: list_id,
+            issuer: caller.clone(),
+            purpose,
+            size: STATUS_LIST_SIZE,
+            next_index: 0,
             created: timestamp,
             updated: timestamp,
         };
 
-        self.did_documents(&did).set(&document);
-        self.address_to_did(&caller).set(&did);
+        self.status_lists(&list_id).set(&list);
+        self.status_list_bits(&list_id).set(zeroed_bitstring(STATUS_LIST_SIZE));
+        self.issuer_status_list(&caller, &purpose).set(list_id);
+        self.issuer_status_lists(&caller).push(&list_id);
+        self.next_status_list_id().set(list_id + 1);
+
+        self.status_list_allocated_event(list_id, &caller, purpose);
+
+        list_id
     }
 
-    // Schema Management
-    #[endpoint(registerSchema)]
-    fn register_schema(
+    // Flips status bits in one batch and keeps the referenced credentials in sync.
+    // Revocation bits can only be set, never cleared.
+    #[endpoint(updateStatusListBits)]
+    fn update_status_list_bits(
         &self,
-        type_: ManagedBuffer,
-        attributes: ManagedVec<SchemaAttribute<Self::Api>>,
-        version: ManagedBuffer,
+        list_id: u64,
+        updates: MultiValueEncoded<MultiValue2<u64, bool>>,
     ) {
         require!(!self.is_paused(), "Contract is paused");
+        require!(!self.status_lists(&list_id).is_empty(), "Status list not found");
+
         let caller = self.blockchain().get_caller();
-        require!(
-            !self.issuers(&caller).is_empty(),
-            "Only registered issuers can create schemas"
-        );
+        let mut list = self.status_lists(&list_id).get();
+        require!(list.issuer == caller, "Only the list issuer can update it");
+
+        let timestamp = self.blockchain().get_block_timestamp();
+        let mut bits = self.status_list_bits(&list_id).get();
+        for update in updates {
+            let (index, value) = update.into_tuple();
+            require!(index < list.next_index, "Status index not assigned");
+            write_status_bit(&mut bits, index, value);
+
+            let credential_id = self.status_list_credential(&list_id, &index).get();
+            let mut credential = self.credentials(&credential_id).get();
+            match list.purpose {
+                StatusPurpose::Revocation => {
+                    require!(value, "Revocation cannot be undone");
+                    if !credential.revoked {
+                        let reason = ManagedBuffer::from("Revoked via status list");
+                        self.record_credential_revocation(credential_id, &mut credential, &caller, &reason);
+                        self.credential_revoked_event(credential_id, &caller, &reason);
+                    }
+                }
+                StatusPurpose::Suspension => {
+                    if value && !self.is_suspended(&credential) {
+                        let reason = ManagedBuffer::from("Suspended via status list");
+                        credential.suspension = Some(CredentialSuspension {
+                            reason: reason.clone(),
+                            suspended_at: timestamp,
+                            suspended_until: None,
+                            suspended_by: caller.clone(),
+                        });
+                        self.credentials(&credential_id).set(&credential);
+                        self.credential_suspended_event(credential_id, &caller, &reason);
+                    } else if !value && credential.suspension.is_some() {
+                        credential.suspension = None;
+                        self.credentials(&credential_id).set(&credential);
+                        self.credential_reinstated_event(credential_id, &caller);
+                    }
+                }
+            }
+        }
+
+        list.updated = timestamp;
+        self.status_list_bits(&list_id).set(&bits);
+        self.status_lists(&list_id).set(&list);
 
-        let schema_id = self.generate_schema_id(&type_, &version);
+        self.status_list_updated_event(list_id, &caller);
+    }
+
+    // `encodedList` of a Bitstring Status List credential
+    #[view(getEncodedStatusList)]
+    fn get_encoded_status_list(&self, list_id: u64) -> ManagedBuffer {
+        require!(!self.status_lists(&list_id).is_empty(), "Status list not found");
+
+        let bits = self.status_list_bits(&list_id).get();
+        multibase_base64url(&gzip_compress(&bits))
+    }
+
+    #[view(getStatus)]
+    fn get_status(&self, list_id: u64, index: u64) -> bool {
+        require!(!self.status_lists(&list_id).is_empty(), "Status list not found");
         require!(
-            self.credential_schemas(&schema_id).is_empty(),
-            "Schema already exists"
+            index < self.status_lists(&list_id).get().size,
+            "Status index out of range"
         );
 
-        let schema = CredentialSchema {
-            id: schema_id.clone(),
-            type_,
-            attributes,
-            issuer: caller,
-            version,
-        };
+        read_status_bit(&self.status_list_bits(&list_id).get(), index)
+    }
 
-        self.credential_schemas(&schema_id).set(&schema);
+    #[view(getIssuerStatusLists)]
+    fn get_issuer_status_lists(&self, issuer: ManagedAddress) -> MultiValueEncoded<u64> {
+        self.issuer_status_lists(&issuer).iter().collect()
     }
 
-    // Credential Management
-    #[endpoint(issueCredential)]
-    fn issue_credential(
+    // Delegation
+    #[endpoint(delegateCredentialIssuance)]
+    fn delegate_credential_issuance(
         &self,
-        holder: ManagedAddress,
-        credential_type: ManagedVec<ManagedBuffer>,
-        schema_id: ManagedBuffer,
-        claims: Claims<Self::Api>,
-        expiration_date: Option<u64>,
-        encryption_key: ManagedBuffer,
-    ) {
+        delegate: ManagedAddress,
+        credential_types: ManagedVec<ManagedBuffer>,
+        valid_until: u64,
+        limits: OptionalValue<DelegationLimits<Self::Api>>,
+    ) -> u64 {
         require!(!self.is_paused(), "Contract is paused");
         let caller = self.blockchain().get_caller();
         require!(
-            !self.issuers(&caller).is_empty(),
-            "Only registered issuers can issue credentials"
+            self.is_active_issuer(&caller),
+            "Only registered issuers can delegate"
         );
+        require!(delegate != caller, "Cannot delegate to yourself");
 
-        // Validate schema
-        let schema = self.credential_schemas(&schema_id).get();
-        self.validate_claims_against_schema(&claims, &schema);
+        let limits = limits.into_option().unwrap_or_else(|| DelegationLimits {
+            max_credentials: None,
+            allowed_schema_ids: ManagedVec::new(),
+            allowed_holders: ManagedVec::new(),
+            max_sub_delegation_depth: 0,
+        });
+        self.require_valid_delegation_terms(&credential_types, valid_until, &limits, 0);
 
         let timestamp = self.blockchain().get_block_timestamp();
-        let credential_id = self.generate_credential_id();
-
-        let proof = self.generate_credential_proof(
-            &caller,
-            &holder,
-            claims.merkle_root.clone(),
-            timestamp,
-        );
-
-        let credential = Credential {
-            id: self.managed_buffer_from_bytes(credential_id.to_boxed_bytes().as_ref()),
-            issuer: caller,
-            holder: holder.clone(),
-            credential_type,
-            schema_id,
-            content_hash: claims.merkle_root.clone(),
-            claims,
-            issuance_date: timestamp,
-            expiration_date,
+        let delegation_id = self.next_delegation_id().get();
+        let delegation = Delegation {
+            id: delegation_id,
+            delegator: caller.clone(),
+            delegate: delegate.clone(),
+            credential_types,
+            valid_until,
             revoked: false,
-            delegation_id: None,
-            encryption_key,
-            proof,
+            parent_id: None,
+            depth: 0,
+            limits,
+            issued_count: 0,
+            created: timestamp,
+            updated: timestamp,
         };
-        let credential_id_u64 = decode_credential_id(credential_id);
 
-        self.credentials(&credential_id_u64).set(&credential);
-        self.holder_credentials(&holder).push(&credential_id_u64);
+        self.delegations(&delegation_id).set(&delegation);
+        self.delegate_to_delegations(&delegate).push(&delegation_id);
+        self.delegator_delegations(&caller).push(&delegation_id);
+        self.next_delegation_id().set(delegation_id + 1);
+
+        self.delegation_granted_event(delegation_id, &caller, &delegate);
+        delegation_id
     }
 
-    // Delegation
-    #[endpoint(delegateCredentialIssuance)]
-    fn delegate_credential_issuance(
+    #[endpoint(subDelegate)]
+    fn sub_delegate(
         &self,
+        parent_id: u64,
         delegate: ManagedAddress,
         credential_types: ManagedVec<ManagedBuffer>,
         valid_until: u64,
-    ) {
+        limits: DelegationLimits<Self::Api>,
+    ) -> u64 {
         require!(!self.is_paused(), "Contract is paused");
         let caller = self.blockchain().get_caller();
         require!(
-            !self.issuers(&caller).is_empty(),
-            "Only registered issuers can delegate"
+            !self.delegations(&parent_id).is_empty(),
+            "Parent delegation not found"
+        );
+        let parent = self.delegations(&parent_id).get();
+        require!(
+            parent.delegate == caller,
+            "Only the delegate can sub-delegate"
         );
+        require!(delegate != caller, "Cannot delegate to yourself");
+        self.verify_delegation(parent_id, &credential_types);
+
+        let depth = parent.depth + 1;
+        self.require_valid_delegation_terms(&credential_types, valid_until, &limits, depth);
+        self.require_within_parent_delegation(&parent, &credential_types, valid_until, &limits);
 
+        let timestamp = self.blockchain().get_block_timestamp();
         let delegation_id = self.next_delegation_id().get();
         let delegation = Delegation {
             id: delegation_id,
-            delegator: caller,
+            delegator: caller.clone(),
             delegate: delegate.clone(),
             credential_types,
             valid_until,
             revoked: false,
+            parent_id: Some(parent_id),
+            depth,
+            limits,
+            issued_count: 0,
+            created: timestamp,
+            updated: timestamp,
         };
 
         self.delegations(&delegation_id).set(&delegation);
         self.delegate_to_delegations(&delegate).push(&delegation_id);
+        self.delegator_delegations(&caller).push(&delegation_id);
         self.next_delegation_id().set(delegation_id + 1);
+
+        self.delegation_granted_event(delegation_id, &caller, &delegate);
+        delegation_id
+    }
+
+    #[endpoint(amendDelegation)]
+    fn amend_delegation(
+        &self,
+        delegation_id: u64,
+        credential_types: ManagedVec<ManagedBuffer>,
+        valid_until: u64,
+        limits: DelegationLimits<Self::Api>,
+    ) {
+        require!(!self.is_paused(), "Contract is paused");
+        let mut delegation = self.require_own_delegation(delegation_id);
+        self.require_valid_delegation_terms(&credential_types, valid_until, &limits, delegation.depth);
+        if let Some(parent_id) = delegation.parent_id {
+            let parent = self.delegations(&parent_id).get();
+            self.require_within_parent_delegation(&parent, &credential_types, valid_until, &limits);
+        }
+
+        delegation.credential_types = credential_types;
+        delegation.valid_until = valid_until;
+        delegation.limits = limits;
+        delegation.updated = self.blockchain().get_block_timestamp();
+        self.delegations(&delegation_id).set(&delegation);
+
+        self.delegation_amended_event(delegation_id, &delegation.delegator);
+    }
+
+    #[endpoint(revokeDelegation)]
+    fn revoke_delegation(&self, delegation_id: u64) {
+        let mut delegation = self.require_own_delegation(delegation_id);
+
+        delegation.revoked = true;
+        delegation.updated = self.blockchain().get_block_timestamp();
+        self.delegations(&delegation_id).set(&delegation);
+
+        self.delegation_revoked_event(delegation_id, &delegation.delegator);
+    }
+
+    #[endpoint(setMaxDelegationDepth)]
+    fn set_max_delegation_depth(&self, max_depth: u32) {
+        self.require_admin();
+        self.max_delegation_depth().set(max_depth);
+    }
+
+    #[view(getDelegationChain)]
+    fn get_delegation_chain(&self, delegation_id: u64) -> MultiValueEncoded<u64> {
+        require!(
+            !self.delegations(&delegation_id).is_empty(),
+            "Delegation not found"
+        );
+        self.delegation_chain(delegation_id).into_iter().collect()
+    }
+
+    #[view(getDelegationsByDelegator)]
+    fn get_delegations_by_delegator(
+        &self,
+        delegator: ManagedAddress,
+        from: usize,
+        size: usize,
+    ) -> MultiValueEncoded<Delegation<Self::Api>> {
+        let mut result = MultiValueEncoded::new();
+        for delegation_id in self.delegator_delegations(&delegator).iter().skip(from).take(size) {
+            result.push(self.delegations(&delegation_id).get());
+        }
+        result
+    }
+
+    #[view(getDelegationsByDelegate)]
+    fn get_delegations_by_delegate(
+        &self,
+        delegate: ManagedAddress,
+        from: usize,
+        size: usize,
+    ) -> MultiValueEncoded<Delegation<Self::Api>> {
+        let mut result = MultiValueEncoded::new();
+        for delegation_id in self.delegate_to_delegations(&delegate).iter().skip(from).take(size) {
+            result.push(self.delegations(&delegation_id).get());
+        }
+        result
     }
 
     // GDPR Compliance
@@ -186,33 +1534,56 @@ pub trait DigitalIdentityContract:
         // Mark credentials for deletion
         let holder_credentials = self.holder_credentials(&caller); // Bind the VecMapper to a variable
         let credentials = holder_credentials.iter();
+        let mut pending = PendingStatusBits::new();
         for credential_id in credentials {
-            self.mark_credential_for_deletion(credential_id);
+            // Credentials revoked by their issuer need no further marking
+            let credential = self.credentials(&credential_id).get();
+            if credential.revoked {
+                continue;
+            }
+            self.mark_credential_for_deletion(credential_id, credential, &caller, &mut pending);
         }
+        self.store_status_bits(&pending);
 
         // Emit event for off-chain deletion of associated data
         self.data_deletion_requested_event(&caller);
     }
 
     #[endpoint(markCredentialForDeletion)]
-    fn mark_credential_for_deletion(
-        &self,
-        credential_id: u64,
-    ) {
-        // Fetch the credential from storage
-        let mut credential = self.credentials(&credential_id).get();
+    fn mark_credential_for_deletion_endpoint(&self, credential_id: u64) {
+        require!(
+            !self.credentials(&credential_id).is_empty(),
+            "Credential not found"
+        );
+        let credential = self.credentials(&credential_id).get();
+        let caller = self.blockchain().get_caller();
+        require!(
+            credential.holder == caller,
+            "Only the holder can mark a credential for deletion"
+        );
 
         // Check if the credential is already revoked or marked for deletion
         require!(
-        !credential.revoked,
-        "Credential is already revoked or marked for deletion"
-    );
+            !credential.revoked,
+            "Credential is already revoked or marked for deletion"
+        );
 
-        // Mark the credential as revoked
-        credential.revoked = true;
+        let mut pending = PendingStatusBits::new();
+        self.mark_credential_for_deletion(credential_id, credential, &caller, &mut pending);
+        self.store_status_bits(&pending);
+    }
 
-        // Update the credential in storage
-        self.credentials(&credential_id).set(&credential);
+    fn mark_credential_for_deletion(
+        &self,
+        credential_id: u64,
+        mut credential: Credential<Self::Api>,
+        holder: &ManagedAddress,
+        pending: &mut PendingStatusBits<Self::Api>,
+    ) {
+        // Mark the credential as revoked
+        let reason = ManagedBuffer::from("Holder requested data deletion");
+        self.record_credential_revocation(credential_id, &mut credential, holder, &reason);
+        self.stage_credential_status_bit(pending, &credential, StatusPurpose::Revocation, true);
 
         // Emit an event for deletion
         self.credential_marked_for_deletion_event(credential_id);
@@ -225,6 +1596,198 @@ pub trait DigitalIdentityContract:
         #[indexed] credential_id: u64,
     );
 
+    #[event("verificationMethodAdded")]
+    fn verification_method_added_event(
+        &self,
+        #[indexed] did: &ManagedBuffer,
+        #[indexed] method_id: &ManagedBuffer,
+    );
+
+    #[event("verificationMethodRemoved")]
+    fn verification_method_removed_event(
+        &self,
+        #[indexed] did: &ManagedBuffer,
+        #[indexed] method_id: &ManagedBuffer,
+    );
+
+    #[event("nextKeyCommitted")]
+    fn next_key_committed_event(
+        &self,
+        #[indexed] did: &ManagedBuffer,
+        #[indexed] commitment: &ManagedBuffer,
+    );
+
+    #[event("keyRotated")]
+    fn key_rotated_event(
+        &self,
+        #[indexed] did: &ManagedBuffer,
+        #[indexed] old_method_id: &ManagedBuffer,
+        #[indexed] new_method_id: &ManagedBuffer,
+    );
+
+    #[event("serviceAdded")]
+    fn service_added_event(
+        &self,
+        #[indexed] did: &ManagedBuffer,
+        #[indexed] service_id: &ManagedBuffer,
+    );
+
+    #[event("serviceRemoved")]
+    fn service_removed_event(
+        &self,
+        #[indexed] did: &ManagedBuffer,
+        #[indexed] service_id: &ManagedBuffer,
+    );
+
+    #[event("verificationRelationshipsUpdated")]
+    fn verification_relationships_updated_event(&self, #[indexed] did: &ManagedBuffer);
+
+    #[event("issuerRegistered")]
+    fn issuer_registered_event(
+        &self,
+        #[indexed] issuer: &ManagedAddress,
+        #[indexed] did: &ManagedBuffer,
+    );
+
+    #[event("issuerSuspended")]
+    fn issuer_suspended_event(&self, #[indexed] issuer: &ManagedAddress);
+
+    #[event("issuerReinstated")]
+    fn issuer_reinstated_event(&self, #[indexed] issuer: &ManagedAddress);
+
+    #[event("issuerRemoved")]
+    fn issuer_removed_event(&self, #[indexed] issuer: &ManagedAddress);
+
+    #[event("schemaRegistered")]
+    fn schema_registered_event(
+        &self,
+        #[indexed] schema_id: &ManagedBuffer,
+        #[indexed] issuer: &ManagedAddress,
+        #[indexed] type_: &ManagedBuffer,
+        version: &ManagedBuffer,
+    );
+
+    #[event("schemaDeprecated")]
+    fn schema_deprecated_event(&self, #[indexed] schema_id: &ManagedBuffer, #[indexed] issuer: &ManagedAddress);
+
+    #[event("didControllersUpdated")]
+    fn did_controllers_updated_event(&self, #[indexed] did: &ManagedBuffer, #[indexed] threshold: u32);
+
+    #[event("didUpdateProposed")]
+    fn did_update_proposed_event(
+        &self,
+        #[indexed] did: &ManagedBuffer,
+        #[indexed] proposal_id: u64,
+        #[indexed] proposer: &ManagedAddress,
+    );
+
+    #[event("didUpdateApproved")]
+    fn did_update_approved_event(
+        &self,
+        #[indexed] did: &ManagedBuffer,
+        #[indexed] proposal_id: u64,
+        #[indexed] approver: &ManagedAddress,
+    );
+
+    #[event("didUpdateExecuted")]
+    fn did_update_executed_event(&self, #[indexed] did: &ManagedBuffer, #[indexed] proposal_id: u64);
+
+    #[event("recoveryGuardiansUpdated")]
+    fn recovery_guardians_updated_event(&self, #[indexed] did: &ManagedBuffer, #[indexed] quorum: u32);
+
+    #[event("recoveryInitiated")]
+    fn recovery_initiated_event(
+        &self,
+        #[indexed] did: &ManagedBuffer,
+        #[indexed] new_controller: &ManagedAddress,
+        #[indexed] guardian: &ManagedAddress,
+    );
+
+    #[event("recoveryApproved")]
+    fn recovery_approved_event(&self, #[indexed] did: &ManagedBuffer, #[indexed] guardian: &ManagedAddress);
+
+    #[event("recoveryCancelled")]
+    fn recovery_cancelled_event(&self, #[indexed] did: &ManagedBuffer, #[indexed] controller: &ManagedAddress);
+
+    #[event("recoveryExecuted")]
+    fn recovery_executed_event(
+        &self,
+        #[indexed] did: &ManagedBuffer,
+        #[indexed] new_controller: &ManagedAddress,
+    );
+
+    #[event("didTransferStarted")]
+    fn did_transfer_started_event(
+        &self,
+        #[indexed] did: &ManagedBuffer,
+        #[indexed] new_controller: &ManagedAddress,
+    );
+
+    #[event("didTransferAccepted")]
+    fn did_transfer_accepted_event(
+        &self,
+        #[indexed] did: &ManagedBuffer,
+        #[indexed] new_controller: &ManagedAddress,
+    );
+
+    #[event("didTransferCancelled")]
+    fn did_transfer_cancelled_event(&self, #[indexed] did: &ManagedBuffer);
+
+    #[event("didDeactivated")]
+    fn did_deactivated_event(&self, #[indexed] did: &ManagedBuffer);
+
+    #[event("credentialRevoked")]
+    fn credential_revoked_event(
+        &self,
+        #[indexed] credential_id: u64,
+        #[indexed] revoked_by: &ManagedAddress,
+        reason: &ManagedBuffer,
+    );
+
+    #[event("credentialSuspended")]
+    fn credential_suspended_event(
+        &self,
+        #[indexed] credential_id: u64,
+        #[indexed] suspended_by: &ManagedAddress,
+        reason: &ManagedBuffer,
+    );
+
+    #[event("credentialReinstated")]
+    fn credential_reinstated_event(
+        &self,
+        #[indexed] credential_id: u64,
+        #[indexed] reinstated_by: &ManagedAddress,
+    );
+
+    #[event("statusListAllocated")]
+    fn status_list_allocated_event(
+        &self,
+        #[indexed] list_id: u64,
+        #[indexed] issuer: &ManagedAddress,
+        #[indexed] purpose: StatusPurpose,
+    );
+
+    #[event("statusListUpdated")]
+    fn status_list_updated_event(&self, #[indexed] list_id: u64, #[indexed] issuer: &ManagedAddress);
+
+    #[event("delegationGranted")]
+    fn delegation_granted_event(
+        &self,
+        #[indexed] delegation_id: u64,
+        #[indexed] delegator: &ManagedAddress,
+        #[indexed] delegate: &ManagedAddress,
+    );
+
+    #[event("delegationAmended")]
+    fn delegation_amended_event(&self, #[indexed] delegation_id: u64, #[indexed] delegator: &ManagedAddress);
+
+    #[event("delegationRevoked")]
+    fn delegation_revoked_event(
+        &self,
+        #[indexed] delegation_id: u64,
+        #[indexed] revoked_by: &ManagedAddress,
+    );
+
     #[event("dataDeleteRequested")]
     fn data_deletion_requested_event(&self, #[indexed] holder: &ManagedAddress);
 
@@ -1,30 +1,235 @@
 use multiversx_sc::imports::*;
 
 extern crate alloc;
-use alloc::string::ToString;
 
 
 use crate::{
     structures::*,
     storage,
+    status_list::PendingStatusBits,
+    schema_validation::*,
 };
 
 fn convert_to_hex<M: ManagedTypeApi>(hash: &ManagedByteArray<M, 32>) -> ManagedBuffer<M> {
     let mut hex_buffer = ManagedBuffer::new();
     for byte in hash.to_byte_array() {
-        hex_buffer.append_bytes(&hex::encode_upper(&[byte]).as_bytes());
+        hex_buffer.append_bytes(hex::encode_upper([byte]).as_bytes());
     }
     hex_buffer
 }
 
+fn join_buffers<M: ManagedTypeApi>(buffers: &ManagedVec<M, ManagedBuffer<M>>) -> ManagedBuffer<M> {
+    buffers.iter().fold(ManagedBuffer::new(), |mut acc, buffer| {
+        if !acc.is_empty() {
+            acc.append(&ManagedBuffer::from(", "));
+        }
+        acc.append(&*buffer);
+        acc
+    })
+}
+
+// Unset attribute constraints are stored as zero or empty values
+fn non_zero(value: u32) -> Option<u32> {
+    (value > 0).then_some(value)
+}
+
+fn non_empty<M: ManagedTypeApi>(buffer: &ManagedBuffer<M>) -> Option<&ManagedBuffer<M>> {
+    (!buffer.is_empty()).then_some(buffer)
+}
+
+// Domain separation between Merkle leaves and inner nodes
+const MERKLE_LEAF_PREFIX: u8 = 0x00;
+const MERKLE_NODE_PREFIX: u8 = 0x01;
+
+const DID_PREFIX: &[u8] = b"did:multiversx:";
+// DIDs created before the `did:multiversx:<id>` form are stored as `did:multiversx<id>`
+const LEGACY_DID_PREFIX: &[u8] = b"did:multiversx";
+
+const BASE58_ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+const MAX_DECODED_KEY_LEN: usize = 128;
+
+// Multicodec varint headers of the supported public key types
+const ED25519_PUB_HEADER: [u8; 2] = [0xed, 0x01];
+const SECP256K1_PUB_HEADER: [u8; 2] = [0xe7, 0x01];
+const BLS12381_G2_PUB_HEADER: [u8; 2] = [0xeb, 0x01];
+
+#[derive(Clone, Copy, PartialEq)]
+enum KeyCodec {
+    Unknown,
+    Ed25519,
+    Secp256k1,
+    Bls12381G2,
+}
+
+// Splits a DID URL into its DID and fragment, e.g. `did:multiversx:AB#key-1`.
+// Without a `#` the whole buffer is treated as the fragment.
+fn split_did_url<M: ManagedTypeApi>(url: &ManagedBuffer<M>) -> (ManagedBuffer<M>, ManagedBuffer<M>) {
+    let bytes = url.to_boxed_bytes();
+    let bytes = bytes.as_slice();
+    match bytes.iter().rposition(|&b| b == b'#') {
+        Some(pos) => (
+            ManagedBuffer::new_from_bytes(&bytes[..pos]),
+            ManagedBuffer::new_from_bytes(&bytes[pos + 1..]),
+        ),
+        None => (ManagedBuffer::new(), url.clone()),
+    }
+}
+
+pub fn parse_digits(bytes: &[u8]) -> Option<u32> {
+    if bytes.is_empty() || !bytes.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    bytes.iter().try_fold(0u32, |acc, &b| {
+        acc.checked_mul(10)?.checked_add((b - b'0') as u32)
+    })
+}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
+
+// Days since 1970-01-01 of a proleptic Gregorian date
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month as i64 + 9) % 12) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+// Parses an ISO-8601 calendar date (`YYYY-MM-DD`)
+pub fn parse_iso8601_date(bytes: &[u8]) -> Option<(i64, u32, u32)> {
+    if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return None;
+    }
+
+    let year = parse_digits(&bytes[0..4])? as i64;
+    let month = parse_digits(&bytes[5..7])?;
+    let day = parse_digits(&bytes[8..10])?;
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return None;
+    }
+    Some((year, month, day))
+}
+
+// Parses an ISO-8601 UTC date-time (`YYYY-MM-DDTHH:MM:SSZ`) into a unix timestamp
+pub fn parse_iso8601_timestamp(bytes: &[u8]) -> Option<u64> {
+    if bytes.len() != 20 || bytes[10] != b'T' || bytes[13] != b':' || bytes[16] != b':' || bytes[19] != b'Z' {
+        return None;
+    }
+
+    let (year, month, day) = parse_iso8601_date(&bytes[0..10])?;
+    let hours = parse_digits(&bytes[11..13])?;
+    let minutes = parse_digits(&bytes[14..16])?;
+    let seconds = parse_digits(&bytes[17..19])?;
+    if hours > 23 || minutes > 59 || seconds > 59 {
+        return None;
+    }
+
+    let days = days_from_civil(year, month, day);
+    if days < 0 {
+        return None;
+    }
+    Some(days as u64 * 86400 + hours as u64 * 3600 + minutes as u64 * 60 + seconds as u64)
+}
+
+// Accepts either an ISO-8601 date-time or plain unix seconds
+fn parse_version_time(bytes: &[u8]) -> Option<u64> {
+    if !bytes.is_empty() && bytes.iter().all(u8::is_ascii_digit) {
+        return core::str::from_utf8(bytes).ok()?.parse::<u64>().ok();
+    }
+    parse_iso8601_timestamp(bytes)
+}
+
+fn decode_base58(encoded: &[u8], output: &mut [u8; MAX_DECODED_KEY_LEN]) -> Option<usize> {
+    // Digits are accumulated little-endian and reversed at the end
+    let mut output_len = 0usize;
+    for &c in encoded {
+        let mut carry = BASE58_ALPHABET.iter().position(|&a| a == c)? as u32;
+        for byte in output[..output_len].iter_mut() {
+            carry += (*byte as u32) * 58;
+            *byte = (carry & 0xff) as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            if output_len == MAX_DECODED_KEY_LEN {
+                return None;
+            }
+            output[output_len] = (carry & 0xff) as u8;
+            output_len += 1;
+            carry >>= 8;
+        }
+    }
+
+    // Every leading '1' stands for a leading zero byte
+    for _ in encoded.iter().take_while(|&&c| c == b'1') {
+        if output_len == MAX_DECODED_KEY_LEN {
+            return None;
+        }
+        output[output_len] = 0;
+        output_len += 1;
+    }
+
+    output[..output_len].reverse();
+    Some(output_len)
+}
+
+// Decodes a `publicKeyMultibase` value (base58btc `z` or base16 `f`) and strips
+// the multicodec header when one is present.
+fn decode_public_key_multibase<M: ManagedTypeApi>(
+    encoded: &ManagedBuffer<M>,
+) -> Option<(KeyCodec, ManagedBuffer<M>)> {
+    let encoded = encoded.to_boxed_bytes();
+    let (prefix, data) = encoded.as_slice().split_first()?;
+
+    let mut decoded = [0u8; MAX_DECODED_KEY_LEN];
+    let decoded_len = match prefix {
+        b'z' => decode_base58(data, &mut decoded)?,
+        b'f' => {
+            if data.len() % 2 != 0 || data.len() / 2 > MAX_DECODED_KEY_LEN {
+                return None;
+            }
+            hex::decode_to_slice(data, &mut decoded[..data.len() / 2]).ok()?;
+            data.len() / 2
+        }
+        _ => return None,
+    };
+    let decoded = &decoded[..decoded_len];
+
+    let headers = [
+        (ED25519_PUB_HEADER, KeyCodec::Ed25519, 32usize),
+        (SECP256K1_PUB_HEADER, KeyCodec::Secp256k1, 33usize),
+        (BLS12381_G2_PUB_HEADER, KeyCodec::Bls12381G2, 96usize),
+    ];
+    for (header, codec, key_len) in headers {
+        if decoded.len() == header.len() + key_len && decoded.starts_with(&header) {
+            return Some((codec, ManagedBuffer::new_from_bytes(&decoded[header.len()..])));
+        }
+    }
+
+    Some((KeyCodec::Unknown, ManagedBuffer::new_from_bytes(decoded)))
+}
+
 #[multiversx_sc::module]
 pub trait HelpersModule: storage::StorageModule {
-    fn generate_did(&self, address: &ManagedAddress) -> ManagedBuffer {
+    fn generate_did(&self, address: &ManagedAddress, salt: &ManagedBuffer) -> ManagedBuffer {
         // Implementation for DID generation following W3C DID specification
-        let mut prefix = ManagedBuffer::from("did:multiversx");
+        let mut prefix = ManagedBuffer::new_from_bytes(DID_PREFIX);
 
-        // Hash the address using SHA-256
-        let hashed_address = self.crypto().sha256(address.as_managed_buffer());
+        // Hash the address and salt using SHA-256
+        let mut seed = address.as_managed_buffer().clone();
+        seed.append(salt);
+        let hashed_address = self.crypto().sha256(&seed);
 
         // Convert ManagedByteArray to hex string manually
         let hex_hash = convert_to_hex(&hashed_address);
@@ -36,50 +241,271 @@ pub trait HelpersModule: storage::StorageModule {
         prefix
     }
 
-    fn generate_schema_id(&self, type_: &ManagedBuffer, version: &ManagedBuffer) -> ManagedBuffer {
-        // Concatenate type and version into a single buffer
-        let mut concatenated = ManagedBuffer::new();
-        concatenated.append(type_);
-        concatenated.append(version);
+    fn legacy_did(&self, did: &ManagedBuffer) -> ManagedBuffer {
+        let mut legacy = ManagedBuffer::new_from_bytes(LEGACY_DID_PREFIX);
+        if let Some(id) = did.copy_slice(DID_PREFIX.len(), did.len().saturating_sub(DID_PREFIX.len())) {
+            legacy.append(&id);
+        }
+        legacy
+    }
+
+    // A generated DID is taken when it exists in either spelling
+    fn is_did_available(&self, did: &ManagedBuffer) -> bool {
+        self.did_documents(did).is_empty() && self.did_documents(&self.legacy_did(did)).is_empty()
+    }
+
+    fn generate_schema_id(
+        &self,
+        issuer_did: &ManagedBuffer,
+        type_: &ManagedBuffer,
+        version: &ManagedBuffer,
+    ) -> ManagedBuffer {
+        // Length-prefixed encoding keeps the issuer DID, type and version apart
+        let encoded = self
+            .serializer()
+            .top_encode_to_managed_buffer(&(issuer_did, type_, version));
 
-        // Hash the concatenated buffer using SHA-256
-        let hash = self.crypto().sha256(&concatenated);
+        // Hash the encoded buffer using SHA-256
+        let hash = self.crypto().sha256(&encoded);
 
         // Convert the hash to a hexadecimal string
         convert_to_hex(&hash)
     }
 
     // Verify Credential
-    fn verify_credential<M: ManagedTypeApi>(
+    fn verify_credential(
         &self,
-        credential: &Credential<M>,
-    ) -> Result<(), ManagedBuffer> {
+        credential_id: u64,
+        credential: &Credential<Self::Api>,
+    ) -> VerificationResult<Self::Api> {
         let current_timestamp = self.blockchain().get_block_timestamp();
+        let mut reasons = ManagedVec::new();
 
         // Check if credential is revoked
-        if credential.revoked {
-            return Err(ManagedBuffer::from("Credential is revoked"));
+        let revoked = credential.revoked;
+        if revoked {
+            let revocation = self.credential_revocations(&credential_id);
+            if revocation.is_empty() {
+                reasons.push(ManagedBuffer::from("Credential is revoked"));
+            } else {
+                reasons.push(sc_format!("Credential is revoked: {}", revocation.get().reason));
+            }
         }
 
         // Check expiration
-        if let Some(expiration_date) = credential.expiration_date {
-            if current_timestamp > expiration_date {
-                return Err(ManagedBuffer::from("Credential has expired"));
+        let expired = matches!(
+            credential.expiration_date,
+            Some(expiration_date) if current_timestamp > expiration_date
+        );
+        if expired {
+            reasons.push(ManagedBuffer::from("Credential has expired"));
+        }
+
+        // Check suspension, which ends on its own once the window has passed
+        let suspended = self.is_suspended(credential);
+        if suspended {
+            if let Some(suspension) = &credential.suspension {
+                reasons.push(sc_format!("Credential is suspended: {}", suspension.reason));
+            }
+        }
+
+        // Check the issuer is still registered and active
+        let issuer_active = self.is_active_issuer(&credential.issuer);
+        if !issuer_active {
+            reasons.push(ManagedBuffer::from("Issuer is not active"));
+        }
+
+        // Report whether the schema still accepts new issuance. This is
+        // informational only: deprecating a schema does not invalidate the
+        // credentials already issued under it.
+        let schema_mapper = self.credential_schemas(&credential.schema_id);
+        let schema_active = !schema_mapper.is_empty() && self.is_schema_active(&schema_mapper.get());
+
+        // Check the delegation the credential was issued under
+        let mut delegation_valid = true;
+        let mut delegation_chain = ManagedVec::new();
+        if let Some(delegation_id) = credential.delegation_id {
+            delegation_chain = self.delegation_chain(delegation_id);
+            if let Some(error) = self.issued_delegation_error(delegation_id, credential.issuance_date) {
+                delegation_valid = false;
+                reasons.push(error);
             }
         }
 
         // Verify proof
-        if !self.verify_credential_proof(credential) {
-            return Err(ManagedBuffer::from("Invalid credential proof"));
+        let proof_valid = self.verify_credential_proof(credential);
+        if !proof_valid {
+            reasons.push(ManagedBuffer::from("Invalid credential proof"));
         }
 
-        // If all checks pass
-        Ok(())
+        VerificationResult {
+            credential_id,
+            valid: reasons.is_empty(),
+            revoked,
+            expired,
+            suspended,
+            issuer_active,
+            schema_active,
+            delegation_valid,
+            proof_valid,
+            delegation_chain,
+            reasons,
+        }
+    }
+
+    // Revoke Credential, keeping its revocation status bit in sync
+    fn revoke_credential(
+        &self,
+        credential_id: u64,
+        credential: &mut Credential<Self::Api>,
+        revoked_by: &ManagedAddress<Self::Api>,
+        reason: &ManagedBuffer<Self::Api>,
+    ) {
+        self.record_credential_revocation(credential_id, credential, revoked_by, reason);
+        self.set_credential_status_bit(credential, StatusPurpose::Revocation, true);
     }
 
-    // Revoke Credential
-    fn revoke_credential(&self, credential: &mut Credential<Self::Api>) {
+    // Updates the credential and its revocation record only, for callers that
+    // write the status bits themselves
+    fn record_credential_revocation(
+        &self,
+        credential_id: u64,
+        credential: &mut Credential<Self::Api>,
+        revoked_by: &ManagedAddress<Self::Api>,
+        reason: &ManagedBuffer<Self::Api>,
+    ) {
         credential.revoked = true;
+        self.credentials(&credential_id).set(&*credential);
+
+        let revocation = CredentialRevocation {
+            reason: reason.clone(),
+            revoked_at: self.blockchain().get_block_timestamp(),
+            revoked_by: revoked_by.clone(),
+        };
+        self.credential_revocations(&credential_id).set(&revocation);
+    }
+
+    fn is_suspended(&self, credential: &Credential<Self::Api>) -> bool {
+        match &credential.suspension {
+            Some(suspension) => match suspension.suspended_until {
+                Some(until) => self.blockchain().get_block_timestamp() < until,
+                None => true,
+            },
+            None => false,
+        }
+    }
+
+    // Takes the next free index of each of the issuer's current status lists
+    fn assign_credential_status(
+        &self,
+        issuer: &ManagedAddress<Self::Api>,
+        credential_id: u64,
+    ) -> ManagedVec<Self::Api, CredentialStatusEntry> {
+        let mut entries = ManagedVec::new();
+        for purpose in [StatusPurpose::Revocation, StatusPurpose::Suspension] {
+            let current_list = self.issuer_status_list(issuer, &purpose);
+            if current_list.is_empty() {
+                continue;
+            }
+
+            // Issuing without a status entry would make the credential unrevocable
+            // through the list, so a full list must be replaced first
+            let list_id = current_list.get();
+            let mut list = self.status_lists(&list_id).get();
+            require!(
+                list.next_index < list.size,
+                "Status list is full, allocate a new one"
+            );
+
+            entries.push(CredentialStatusEntry {
+                list_id,
+                index: list.next_index,
+                purpose,
+            });
+            self.status_list_credential(&list_id, &list.next_index).set(credential_id);
+            list.next_index += 1;
+            self.status_lists(&list_id).set(&list);
+        }
+        entries
+    }
+
+    fn set_credential_status_bit(
+        &self,
+        credential: &Credential<Self::Api>,
+        purpose: StatusPurpose,
+        value: bool,
+    ) {
+        let mut pending = PendingStatusBits::new();
+        self.stage_credential_status_bit(&mut pending, credential, purpose, value);
+        self.store_status_bits(&pending);
+    }
+
+    // Writes the credential's status bit into the in-memory copy of its list
+    fn stage_credential_status_bit(
+        &self,
+        pending: &mut PendingStatusBits<Self::Api>,
+        credential: &Credential<Self::Api>,
+        purpose: StatusPurpose,
+        value: bool,
+    ) {
+        for entry in credential.status.iter() {
+            if entry.purpose != purpose {
+                continue;
+            }
+
+            let position = match pending.position(entry.list_id) {
+                Some(position) => position,
+                None => pending.push(entry.list_id, self.status_list_bits(&entry.list_id).get()),
+            };
+            pending.write(position, entry.index, value);
+        }
+    }
+
+    fn store_status_bits(&self, pending: &PendingStatusBits<Self::Api>) {
+        let timestamp = self.blockchain().get_block_timestamp();
+        for (list_id, bits) in pending.list_ids.iter().zip(pending.bits.iter()) {
+            self.status_list_bits(&list_id).set(&*bits);
+
+            let mut list = self.status_lists(&list_id).get();
+            list.updated = timestamp;
+            self.status_lists(&list_id).set(&list);
+        }
+    }
+
+    // The issuer, or a delegate holding a valid delegation (chain) from the
+    // issuer whose types, schemas and holders cover the credential
+    fn can_manage_credential(
+        &self,
+        address: &ManagedAddress<Self::Api>,
+        credential: &Credential<Self::Api>,
+    ) -> bool {
+        if *address == credential.issuer {
+            return true;
+        }
+
+        // Sub-delegates qualify through a chain rooted at the issuer
+        self.delegate_to_delegations(address).iter().any(|delegation_id| {
+            let chain = self.delegation_chain(delegation_id);
+            self.delegations(&chain.get(0)).get().delegator == credential.issuer
+                && chain.iter().all(|id| {
+                    let delegation = self.delegations(&id).get();
+                    self.delegation_scope_covers(&delegation.limits, &credential.schema_id, &credential.holder)
+                })
+                && self
+                    .delegation_error(delegation_id, &credential.credential_type)
+                    .is_none()
+        })
+    }
+
+    // Whether the schema and holder limits of a delegation cover a credential
+    fn delegation_scope_covers(
+        &self,
+        limits: &DelegationLimits<Self::Api>,
+        schema_id: &ManagedBuffer<Self::Api>,
+        holder: &ManagedAddress<Self::Api>,
+    ) -> bool {
+        (limits.allowed_schema_ids.is_empty() || limits.allowed_schema_ids.contains(schema_id))
+            && (limits.allowed_holders.is_empty() || limits.allowed_holders.contains(holder))
     }
 
     // Helper: Generate Credential ID
@@ -91,55 +517,197 @@ pub trait HelpersModule: storage::StorageModule {
         ManagedBuffer::from(&id_bytes)
     }
 
-    // Helper: Generate Credential Proof
-    fn generate_credential_proof(
+    // Helper: Hash of the credential payload the issuer signs off-chain
+    fn credential_signing_message(
         &self,
         issuer: &ManagedAddress<Self::Api>,
         holder: &ManagedAddress<Self::Api>,
-        merkle_root: ManagedBuffer<Self::Api>,
+        credential_type: &ManagedVec<Self::Api, ManagedBuffer<Self::Api>>,
+        schema_id: &ManagedBuffer<Self::Api>,
+        merkle_root: &ManagedBuffer<Self::Api>,
+        expiration_date: Option<u64>,
+        delegation_id: Option<u64>,
+    ) -> ManagedBuffer<Self::Api> {
+        let payload = CredentialPayload {
+            contract: self.blockchain().get_sc_address(),
+            issuer: issuer.clone(),
+            holder: holder.clone(),
+            credential_type: credential_type.clone(),
+            schema_id: schema_id.clone(),
+            merkle_root: merkle_root.clone(),
+            expiration_date,
+            delegation_id,
+        };
+        let encoded = self.serializer().top_encode_to_managed_buffer(&payload);
+
+        self.crypto().sha256(&encoded).as_managed_buffer().clone()
+    }
+
+    fn find_verification_method(
+        &self,
+        document: &DidDocument<Self::Api>,
+        fragment: &ManagedBuffer<Self::Api>,
+    ) -> Option<VerificationMethod<Self::Api>> {
+        document
+            .verification_methods
+            .iter()
+            .find(|method| split_did_url(&method.id).1 == *fragment)
+    }
+
+    // Helper: Check a signature with the key of a verification method.
+    // `message` is the 32-byte SHA-256 digest of the top-encoded payload.
+    // Ed25519 and BLS sign the digest itself; secp256k1 signs it as a
+    // prehashed ECDSA input, i.e. plain ECDSA-SHA256 over the payload.
+    // Ed25519 and BLS failures abort the transaction inside the VM.
+    fn verify_signature(
+        &self,
+        method: &VerificationMethod<Self::Api>,
+        message: &ManagedBuffer<Self::Api>,
+        signature: &ManagedBuffer<Self::Api>,
+    ) -> bool {
+        let (codec, key) = match decode_public_key_multibase(&method.public_key_multibase) {
+            Some(decoded) => decoded,
+            None => return false,
+        };
+
+        let codec = match method.type_.to_boxed_bytes().as_slice() {
+            b"Ed25519VerificationKey2018" | b"Ed25519VerificationKey2020" => KeyCodec::Ed25519,
+            b"EcdsaSecp256k1VerificationKey2019" => KeyCodec::Secp256k1,
+            b"Bls12381G2Key2020" => KeyCodec::Bls12381G2,
+            b"Multikey" => codec,
+            _ => KeyCodec::Unknown,
+        };
+
+        match codec {
+            KeyCodec::Ed25519 => {
+                self.crypto().verify_ed25519(&key, message, signature);
+                true
+            }
+            KeyCodec::Secp256k1 => self.crypto().verify_custom_secp256k1(
+                &key,
+                message,
+                signature,
+                MessageHashType::ECDSAPlainMsg,
+            ),
+            KeyCodec::Bls12381G2 => {
+                self.crypto().verify_bls(&key, message, signature);
+                true
+            }
+            KeyCodec::Unknown => false,
+        }
+    }
+
+    // Helper: DID whose key signs the credential. Issuers sign with their
+    // registered DID, delegates with a DID they control.
+    fn signing_did(
+        &self,
+        issuer: &ManagedAddress<Self::Api>,
+        signer: &ManagedAddress<Self::Api>,
+        verification_method: &ManagedBuffer<Self::Api>,
+    ) -> ManagedBuffer<Self::Api> {
+        let did = split_did_url(verification_method).0;
+        if *signer == *issuer {
+            let issuer_did = self.issuers(issuer).get().did;
+            require!(
+                did.is_empty() || did == issuer_did,
+                "Verification method does not belong to the issuer DID"
+            );
+            return issuer_did;
+        }
+
+        let did = if did.is_empty() {
+            require!(!self.address_to_did(signer).is_empty(), "Delegate has no DID");
+            self.address_to_did(signer).get()
+        } else {
+            did
+        };
+        require!(
+            self.did_by_controller(signer).contains(&did),
+            "Delegate must control the signing DID"
+        );
+        require!(!self.did_documents(&did).get().deactivated, "Signing DID is deactivated");
+        did
+    }
+
+    // Helper: Build the proof of a credential from the signer's signature
+    fn build_credential_proof(
+        &self,
+        signer_did: &ManagedBuffer<Self::Api>,
+        verification_method: &ManagedBuffer<Self::Api>,
+        message: &ManagedBuffer<Self::Api>,
+        signature: ManagedBuffer<Self::Api>,
         created: u64,
     ) -> CredentialProof<Self::Api> {
-        let mut data = ManagedBuffer::new();
-        data.append(&issuer.as_managed_buffer());
-        data.append(&holder.as_managed_buffer());
-        data.append(&merkle_root);
-        data.append(&ManagedBuffer::from(&created.to_be_bytes()));
+        let fragment = split_did_url(verification_method).1;
+        let document = self.did_documents(signer_did).get();
+        let method = match self.find_verification_method(&document, &fragment) {
+            Some(method) => method,
+            None => sc_panic!("Unknown verification method: {}", fragment),
+        };
+        require!(
+            document
+                .assertion_method
+                .iter()
+                .any(|id| split_did_url(&id).1 == fragment),
+            "Verification method is not an assertion method"
+        );
+        require!(
+            self.verify_signature(&method, message, &signature),
+            "Invalid issuer signature"
+        );
 
-        let signature_arr =self.crypto().sha256(&data);
-        let signature = convert_to_hex(&signature_arr);
+        let mut method_url = signer_did.clone();
+        method_url.append_bytes(b"#");
+        method_url.append(&fragment);
 
         CredentialProof {
-            type_: ManagedBuffer::from("ProofType"), // Define your proof type
+            type_: method.type_,
             created,
-            verification_method: issuer.as_managed_buffer().clone(),
+            verification_method: method_url,
             signature,
         }
     }
 
     // Helper: Verify Credential Proof
-    fn verify_credential_proof<M: ManagedTypeApi>(&self, credential: &Credential<M>) -> bool {
-        let mut data = ManagedBuffer::new();
-
-        // Append buffers to `data`
-        data.append(&ManagedBuffer::from(credential.issuer.as_managed_buffer().to_boxed_bytes()));
-        data.append(&ManagedBuffer::from(credential.holder.as_managed_buffer().to_boxed_bytes()));
+    fn verify_credential_proof(&self, credential: &Credential<Self::Api>) -> bool {
+        let (did, fragment) = split_did_url(&credential.proof.verification_method);
+        if self.did_documents(&did).is_empty() {
+            return false;
+        }
 
-        data.append(&ManagedBuffer::from(&credential.proof.created.to_be_bytes()));
+        let document = self.did_documents(&did).get();
+        let method = match self.find_key_valid_at(&document, &fragment, credential.proof.created) {
+            Some(method) => method,
+            None => return false,
+        };
 
-        let encryption_key = ManagedBuffer::from(credential.encryption_key.to_boxed_bytes());
-        let signature = ManagedBuffer::from(credential.proof.signature.to_boxed_bytes());
+        let message = self.credential_signing_message(
+            &credential.issuer,
+            &credential.holder,
+            &credential.credential_type,
+            &credential.schema_id,
+            &credential.claims.merkle_root,
+            credential.expiration_date,
+            credential.delegation_id,
+        );
 
+        self.verify_signature(&method, &message, &credential.proof.signature)
+    }
 
-        let is_valid_signature = {
-            self.crypto().verify_bls(
-                &encryption_key,
-                &data,
-                &signature,
-            );
-            true // or false depending on your specific logic
-        };
+    // Deprecated schemas stay usable for issuance during the grace period
+    fn require_schema_usable(&self, schema: &CredentialSchema<Self::Api>) {
+        require!(self.is_schema_active(schema), "Schema is deprecated");
+    }
 
-        is_valid_signature
+    // A deprecated schema stays active until its grace period has passed
+    fn is_schema_active(&self, schema: &CredentialSchema<Self::Api>) -> bool {
+        match schema.deprecated_at {
+            Some(deprecated_at) => {
+                let grace_period_end = deprecated_at + self.schema_deprecation_grace_period().get();
+                self.blockchain().get_block_timestamp() < grace_period_end
+            }
+            None => true,
+        }
     }
 
     fn validate_claims_against_schema(
@@ -147,106 +715,1454 @@ pub trait HelpersModule: storage::StorageModule {
         claims: &Claims<Self::Api>,
         schema: &CredentialSchema<Self::Api>,
     ) {
-        // Iterate through schema attributes
         for attribute in schema.attributes.iter() {
-            // Check if the attribute exists in the claims
-            let claim_value = claims.data.iter().find(|claim| claim.attribute == attribute.name);
-
-            if attribute.required && claim_value.is_none() {
+            if attribute.required && !claims.data.iter().any(|claim| claim.attribute == attribute.name) {
                 sc_panic!("Missing required attribute: {}", attribute.name);
             }
+        }
 
-            if let Some(claim) = claim_value {
-                // Validate type consistency (example: assuming data type validation can be enforced)
-                match attribute.data_type.to_string().as_str() {
-                    "string" => { /* Additional checks for string type, if needed */ }
-                    "integer" => {
-                        if !self.is_integer(&claim.value) {
-                            sc_panic!(
-                            "Type mismatch for attribute {}: expected integer, found {}",
-                            attribute.name,
-                            claim.value
-                        );
-                        }
-                    }
-                    "boolean" => {
-                        if !self.is_boolean(&claim.value) {
-                            sc_panic!(
-                            "Type mismatch for attribute {}: expected boolean, found {}",
-                            attribute.name,
-                            claim.value
-                        );
-                        }
-                    }
-                    _ => {
-                        sc_panic!(
-                        "Unsupported data type for attribute {}: {}",
-                        attribute.name,
-                        attribute.data_type
-                    );
-                    }
+        // Every claim is checked, including repeats of the same attribute
+        for (index, claim) in claims.data.iter().enumerate() {
+            let attribute = schema
+                .attributes
+                .iter()
+                .find(|attribute| attribute.name == claim.attribute);
+            let hash_only = self.is_hash_only_claim(claims, schema, &claim.attribute);
+            let duplicate = claims
+                .data
+                .iter()
+                .take(index)
+                .any(|earlier| earlier.attribute == claim.attribute);
+            if duplicate && (schema.strict || hash_only) {
+                sc_panic!("Duplicate attribute: {}", claim.attribute);
+            }
+
+            match attribute {
+                None if !schema.additional_properties => {
+                    sc_panic!("Attribute not declared in schema: {}", claim.attribute)
                 }
-                // Possibility to add constraints
+                // Without values only the shape of the claims can be checked
+                _ if hash_only => self.validate_hash_only_claim(&claim),
+                Some(attribute) => self.validate_claim_value(&attribute, &claim.value),
+                None => {}
             }
         }
     }
 
-    fn is_integer(&self, value: &ManagedBuffer<Self::Api>) -> bool {
-        value.to_string().parse::<i64>().is_ok()
+    fn is_hash_only_claim(
+        &self,
+        claims: &Claims<Self::Api>,
+        schema: &CredentialSchema<Self::Api>,
+        attribute_name: &ManagedBuffer<Self::Api>,
+    ) -> bool {
+        claims.hash_only
+            || self
+                .claim_disclosure(schema, attribute_name)
+                .is_some_and(Disclosure::is_hash_only)
     }
 
-    fn is_boolean(&self, value: &ManagedBuffer<Self::Api>) -> bool {
-        matches!(value.to_string().as_str(), "true" | "false")
+    // Disclosure of a declared attribute; undeclared attributes have none
+    fn claim_disclosure(
+        &self,
+        schema: &CredentialSchema<Self::Api>,
+        attribute_name: &ManagedBuffer<Self::Api>,
+    ) -> Option<Disclosure> {
+        schema
+            .attributes
+            .iter()
+            .find(|attribute| attribute.name == *attribute_name)
+            .map(|attribute| self.require_disclosure(&attribute))
     }
 
-    fn managed_buffer_from_bytes(&self, bytes: &[u8]) -> ManagedBuffer<Self::Api> {
-        ManagedBuffer::new_from_bytes(bytes)
+    fn is_revealable_claim(
+        &self,
+        schema: &CredentialSchema<Self::Api>,
+        attribute_name: &ManagedBuffer<Self::Api>,
+    ) -> bool {
+        self.claim_disclosure(schema, attribute_name)
+            .is_none_or(Disclosure::is_revealable)
     }
 
-    fn verify_delegation(
+    // Leaf hash of a claim: sha256(0x00 || attribute || value || salt), each length-prefixed
+    fn claim_leaf_hash(
         &self,
-        delegation_id: u64,
-        credential: &Credential<Self::Api>,
-    ) -> bool {
-        let delegation = self.delegations(&delegation_id).get();
+        attribute: &ManagedBuffer<Self::Api>,
+        value: &ManagedBuffer<Self::Api>,
+        salt: &ManagedBuffer<Self::Api>,
+    ) -> ManagedBuffer<Self::Api> {
+        let mut leaf = ManagedBuffer::new_from_bytes(&[MERKLE_LEAF_PREFIX]);
+        leaf.append(
+            &self
+                .serializer()
+                .top_encode_to_managed_buffer(&(attribute, value, salt)),
+        );
+        self.crypto().sha256(&leaf).as_managed_buffer().clone()
+    }
 
-        // Check if delegation is revoked
-        if delegation.revoked {
-            sc_panic!("Delegation {} is revoked", delegation_id);
+    // Sibling pairs are hashed in sorted order, so proofs need no left/right flags
+    fn merkle_parent(
+        &self,
+        left: &ManagedBuffer<Self::Api>,
+        right: &ManagedBuffer<Self::Api>,
+    ) -> ManagedBuffer<Self::Api> {
+        let (first, second) = if left.to_boxed_bytes().as_slice() <= right.to_boxed_bytes().as_slice() {
+            (left, right)
+        } else {
+            (right, left)
+        };
+
+        let mut node = ManagedBuffer::new_from_bytes(&[MERKLE_NODE_PREFIX]);
+        node.append(first);
+        node.append(second);
+        self.crypto().sha256(&node).as_managed_buffer().clone()
+    }
+
+    // An odd node at the end of a level is carried up unchanged
+    fn merkle_root(&self, leaves: &ManagedVec<Self::Api, ManagedBuffer<Self::Api>>) -> ManagedBuffer<Self::Api> {
+        if leaves.is_empty() {
+            return self.crypto().sha256(ManagedBuffer::new()).as_managed_buffer().clone();
         }
 
-        // Check if delegation is expired
-        let current_timestamp = self.blockchain().get_block_timestamp();
-        if current_timestamp > delegation.valid_until {
-            sc_panic!("Delegation {} has expired", delegation_id);
+        let mut level = leaves.clone();
+        while level.len() > 1 {
+            let mut next = ManagedVec::new();
+            let mut index = 0;
+            while index < level.len() {
+                if index + 1 < level.len() {
+                    next.push(self.merkle_parent(&level.get(index), &level.get(index + 1)));
+                } else {
+                    next.push((*level.get(index)).clone());
+                }
+                index += 2;
+            }
+            level = next;
         }
+        (*level.get(0)).clone()
+    }
 
-        // Check if credential type is allowed under the delegation
-        let credential_type_match = credential
-            .credential_type
-            .iter()
-            .all(|type_| delegation.credential_types.contains(&*type_));
+    fn verify_merkle_proof(
+        &self,
+        leaf: ManagedBuffer<Self::Api>,
+        proof_path: impl Iterator<Item = ManagedBuffer<Self::Api>>,
+        merkle_root: &ManagedBuffer<Self::Api>,
+    ) -> bool {
+        let mut node = leaf;
+        for sibling in proof_path {
+            node = self.merkle_parent(&node, &sibling);
+        }
+        node == *merkle_root
+    }
 
-        if !credential_type_match {
+    // Recomputes the hash of every disclosed claim and the Merkle root over all
+    // revealable claims; caller-supplied hashes and root must match when present
+    fn seal_claims(&self, claims: &mut Claims<Self::Api>, schema: &CredentialSchema<Self::Api>) {
+        let mut sealed = ManagedVec::new();
+        let mut leaves = ManagedVec::new();
+        for claim in claims.data.iter() {
+            let hash_only = self.is_hash_only_claim(claims, schema, &claim.attribute);
+            let hash = if hash_only {
+                claim.hash.clone()
+            } else {
+                let hash = self.claim_leaf_hash(&claim.attribute, &claim.value, &claim.salt);
+                if !claim.hash.is_empty() && claim.hash != hash {
+                    sc_panic!("Hash mismatch for attribute {}", claim.attribute);
+                }
+                hash
+            };
 
-            let joined_types = delegation
-                .credential_types
-                .iter()
-                .fold(ManagedBuffer::new(), |mut acc, t| {
-                    if !acc.is_empty() {
-                        acc.append(&ManagedBuffer::from(", "));
-                    }
-                    acc.append(&*t);
-                    acc
-                });
+            // Only the hash of a hash-only claim is ever stored
+            let (value, salt) = if hash_only {
+                (ManagedBuffer::new(), ManagedBuffer::new())
+            } else {
+                (claim.value.clone(), claim.salt.clone())
+            };
+            if self.is_revealable_claim(schema, &claim.attribute) {
+                leaves.push(hash.clone());
+            }
+            sealed.push(Claim {
+                attribute: claim.attribute.clone(),
+                value,
+                salt,
+                hash,
+            });
+        }
+
+        let merkle_root = self.merkle_root(&leaves);
+        require!(
+            claims.merkle_root.is_empty() || claims.merkle_root == merkle_root,
+            "Merkle root does not match the claims"
+        );
+        claims.data = sealed;
+        claims.merkle_root = merkle_root;
+    }
+
+    fn require_disclosure(&self, attribute: &SchemaAttribute<Self::Api>) -> Disclosure {
+        match Disclosure::parse(attribute.attribute_type.to_boxed_bytes().as_slice()) {
+            Some(disclosure) => disclosure,
+            None => sc_panic!(
+                "Unsupported attribute type for attribute {}: {}",
+                attribute.name,
+                attribute.attribute_type
+            ),
+        }
+    }
 
+    // Hash-only claims must never carry their plaintext value, nor the salt that
+    // would let anyone test guesses against the hash
+    fn validate_hash_only_claim(&self, claim: &Claim<Self::Api>) {
+        if !claim.value.is_empty() {
             sc_panic!(
-                "Delegation {} only permits issuance of credential type: {}",
-                delegation_id,
-                joined_types
+                "Attribute {} must be submitted as a hash only, not the value",
+                claim.attribute
+            );
+        }
+        if !claim.salt.is_empty() {
+            sc_panic!(
+                "Attribute {}: keep the salt of a hash-only claim off-chain",
+                claim.attribute
+            );
+        }
+        if claim.hash.len() != CLAIM_HASH_LEN {
+            sc_panic!(
+                "Attribute {}: hash must be {} bytes",
+                claim.attribute,
+                (CLAIM_HASH_LEN as u32)
             );
         }
+    }
 
-        true
+    fn require_data_type(
+        &self,
+        attribute_name: &ManagedBuffer<Self::Api>,
+        data_type: &ManagedBuffer<Self::Api>,
+    ) -> DataType {
+        match DataType::parse(data_type.to_boxed_bytes().as_slice()) {
+            Some(data_type) => data_type,
+            None => sc_panic!(
+                "Unsupported data type for attribute {}: {}",
+                attribute_name,
+                data_type
+            ),
+        }
+    }
+
+    // Type of the values rules apply to: the item type for lists
+    fn value_type(&self, attribute: &SchemaAttribute<Self::Api>) -> (DataType, ManagedBuffer<Self::Api>) {
+        let data_type = self.require_data_type(&attribute.name, &attribute.data_type);
+        if data_type != DataType::List {
+            return (data_type, attribute.data_type.clone());
+        }
+
+        let item_type = match non_empty(&attribute.constraints.item_type) {
+            Some(item_type) => item_type.clone(),
+            None => sc_panic!("Attribute {}: lists require an item type", attribute.name),
+        };
+        let data_type = self.require_data_type(&attribute.name, &item_type);
+        if data_type == DataType::List {
+            sc_panic!("Attribute {}: nested lists are not supported", attribute.name);
+        }
+        (data_type, item_type)
     }
-}
\ No newline at end of file
+
+    // Rejects schema rules that could never be satisfied or do not fit the type
+    fn validate_schema_attributes(&self, attributes: &ManagedVec<SchemaAttribute<Self::Api>>) {
+        for (index, attribute) in attributes.iter().enumerate() {
+            let name = &attribute.name;
+            let constraints = &attribute.constraints;
+            if attributes.iter().take(index).any(|earlier| earlier.name == *name) {
+                sc_panic!("Duplicate schema attribute: {}", name);
+            }
+            if self.require_disclosure(&attribute) == Disclosure::Predicate
+                && self.require_data_type(name, &attribute.data_type) != DataType::Boolean
+            {
+                sc_panic!("Attribute {}: predicates must be booleans", name);
+            }
+            let is_list = self.require_data_type(name, &attribute.data_type) == DataType::List;
+            let (value_type, value_type_name) = self.value_type(&attribute);
+
+            if value_type == DataType::Enum && constraints.allowed_values.is_empty() {
+                sc_panic!("Attribute {}: enums require allowed values", name);
+            }
+            for value in constraints.allowed_values.iter() {
+                if !conforms_to(value_type, value.to_boxed_bytes().as_slice()) {
+                    sc_panic!(
+                        "Attribute {}: allowed value {} is not a valid {}",
+                        name,
+                        *value,
+                        value_type_name
+                    );
+                }
+            }
+            if let (Some(min), Some(max)) = (non_zero(constraints.min_length), non_zero(constraints.max_length)) {
+                if min > max {
+                    sc_panic!("Attribute {}: minimum length exceeds maximum length", name);
+                }
+            }
+            if !is_list && (constraints.min_items > 0 || constraints.max_items > 0) {
+                sc_panic!("Attribute {}: item counts only apply to lists", name);
+            }
+            if let (Some(min), Some(max)) = (non_zero(constraints.min_items), non_zero(constraints.max_items)) {
+                if min > max {
+                    sc_panic!("Attribute {}: minimum items exceed maximum items", name);
+                }
+            }
+            if let Some(pattern) = non_empty(&constraints.pattern) {
+                if !is_valid_pattern(pattern.to_boxed_bytes().as_slice()) {
+                    sc_panic!("Attribute {}: invalid or too complex pattern {}", name, pattern);
+                }
+            }
+            if let Some(max_decimals) = non_zero(constraints.max_decimals) {
+                if value_type != DataType::Decimal || max_decimals > MAX_DECIMALS {
+                    sc_panic!(
+                        "Attribute {}: decimal places apply to decimals, up to {}",
+                        name,
+                        MAX_DECIMALS
+                    );
+                }
+            }
+
+            let minimum = self.schema_bound(name, value_type, &value_type_name, non_empty(&constraints.minimum));
+            let maximum = self.schema_bound(name, value_type, &value_type_name, non_empty(&constraints.maximum));
+            if let (Some(min), Some(max)) = (minimum, maximum) {
+                if min > max {
+                    sc_panic!("Attribute {}: minimum exceeds maximum", name);
+                }
+            }
+        }
+    }
+
+    fn schema_bound(
+        &self,
+        attribute_name: &ManagedBuffer<Self::Api>,
+        value_type: DataType,
+        value_type_name: &ManagedBuffer<Self::Api>,
+        bound: Option<&ManagedBuffer<Self::Api>>,
+    ) -> Option<i128> {
+        let bound = bound?;
+        if !value_type.is_ordered() {
+            sc_panic!(
+                "Attribute {}: minimum and maximum do not apply to {}",
+                attribute_name,
+                value_type_name
+            );
+        }
+        match ordered_value(value_type, bound.to_boxed_bytes().as_slice()) {
+            Some(value) => Some(value),
+            None => sc_panic!(
+                "Attribute {}: bound {} is not a valid {}",
+                attribute_name,
+                bound,
+                value_type_name
+            ),
+        }
+    }
+
+    fn validate_claim_value(
+        &self,
+        attribute: &SchemaAttribute<Self::Api>,
+        value: &ManagedBuffer<Self::Api>,
+    ) {
+        let is_list = self.require_data_type(&attribute.name, &attribute.data_type) == DataType::List;
+        let (value_type, value_type_name) = self.value_type(attribute);
+        if !is_list {
+            self.validate_scalar_value(&attribute.name, value_type, &value_type_name, &attribute.constraints, value);
+            return;
+        }
+
+        let bytes = value.to_boxed_bytes();
+        let mut count = 0u32;
+        for item in list_items(bytes.as_slice()) {
+            let label = sc_format!("{}[{}]", attribute.name, count);
+            let item = ManagedBuffer::new_from_bytes(item);
+            self.validate_scalar_value(&label, value_type, &value_type_name, &attribute.constraints, &item);
+            count += 1;
+        }
+
+        let constraints = &attribute.constraints;
+        if let Some(min_items) = non_zero(constraints.min_items) {
+            if count < min_items {
+                sc_panic!("Attribute {}: must have at least {} items", attribute.name, min_items);
+            }
+        }
+        if let Some(max_items) = non_zero(constraints.max_items) {
+            if count > max_items {
+                sc_panic!("Attribute {}: must have at most {} items", attribute.name, max_items);
+            }
+        }
+    }
+
+    fn validate_scalar_value(
+        &self,
+        label: &ManagedBuffer<Self::Api>,
+        value_type: DataType,
+        value_type_name: &ManagedBuffer<Self::Api>,
+        constraints: &AttributeConstraints<Self::Api>,
+        value: &ManagedBuffer<Self::Api>,
+    ) {
+        let bytes = value.to_boxed_bytes();
+        let bytes = bytes.as_slice();
+        if !conforms_to(value_type, bytes) {
+            sc_panic!(
+                "Type mismatch for attribute {}: expected {}, found {}",
+                label,
+                value_type_name,
+                value
+            );
+        }
+
+        let length = char_count(bytes) as u32;
+        if let Some(min_length) = non_zero(constraints.min_length) {
+            if length < min_length {
+                sc_panic!("Attribute {}: must be at least {} characters long", label, min_length);
+            }
+        }
+        if let Some(max_length) = non_zero(constraints.max_length) {
+            if length > max_length {
+                sc_panic!("Attribute {}: must be at most {} characters long", label, max_length);
+            }
+        }
+
+        if !constraints.allowed_values.is_empty() && !constraints.allowed_values.contains(value) {
+            sc_panic!(
+                "Attribute {}: must be one of {}",
+                label,
+                join_buffers(&constraints.allowed_values)
+            );
+        }
+
+        if let Some(pattern) = non_empty(&constraints.pattern) {
+            if !matches_pattern(pattern.to_boxed_bytes().as_slice(), bytes) {
+                sc_panic!("Attribute {}: does not match pattern {}", label, pattern);
+            }
+        }
+
+        if let Some(max_decimals) = non_zero(constraints.max_decimals) {
+            let decimals = parse_decimal(bytes).map_or(0, |(_, decimals)| decimals);
+            if decimals > max_decimals {
+                sc_panic!("Attribute {}: at most {} decimal places allowed", label, max_decimals);
+            }
+        }
+
+        if let Some(value) = ordered_value(value_type, bytes) {
+            if let Some(minimum) = non_empty(&constraints.minimum) {
+                if ordered_value(value_type, minimum.to_boxed_bytes().as_slice()).is_some_and(|min| value < min) {
+                    sc_panic!("Attribute {}: must be at least {}", label, minimum);
+                }
+            }
+            if let Some(maximum) = non_empty(&constraints.maximum) {
+                if ordered_value(value_type, maximum.to_boxed_bytes().as_slice()).is_some_and(|max| value > max) {
+                    sc_panic!("Attribute {}: must be at most {}", label, maximum);
+                }
+            }
+        }
+    }
+
+    fn managed_buffer_from_bytes(&self, bytes: &[u8]) -> ManagedBuffer<Self::Api> {
+        ManagedBuffer::new_from_bytes(bytes)
+    }
+
+    fn require_admin(&self) {
+        require!(
+            self.blockchain().get_caller() == self.admin().get(),
+            "Only admin can perform this action"
+        );
+    }
+
+    fn is_active_issuer(&self, address: &ManagedAddress) -> bool {
+        let issuer = self.issuers(address);
+        if issuer.is_empty() {
+            return false;
+        }
+
+        let profile = issuer.get();
+        let document = self.did_documents(&profile.did).get();
+        profile.status == IssuerStatus::Active
+            && !document.deactivated
+            && document.controllers.contains(address)
+    }
+
+    fn require_did_controller(&self, did: &ManagedBuffer) -> DidDocument<Self::Api> {
+        require!(!self.did_documents(did).is_empty(), "DID not found");
+
+        let document = self.did_documents(did).get();
+        require!(
+            document.controllers.contains(&self.blockchain().get_caller()),
+            "Only a DID controller can update the document"
+        );
+        require!(!document.deactivated, "DID is deactivated");
+        document
+    }
+
+    // Updates made directly by one controller, without a proposal
+    fn require_direct_did_update(&self, did: &ManagedBuffer) -> DidDocument<Self::Api> {
+        let document = self.require_did_controller(did);
+        require!(
+            document.threshold <= 1,
+            "DID updates require threshold approval"
+        );
+        document
+    }
+
+    fn count_controller_approvals(
+        &self,
+        document: &DidDocument<Self::Api>,
+        approvals: &ManagedVec<Self::Api, ManagedAddress<Self::Api>>,
+    ) -> u32 {
+        approvals
+            .iter()
+            .filter(|approver| document.controllers.contains(approver))
+            .count() as u32
+    }
+
+    fn set_recovery_config(
+        &self,
+        document: &DidDocument<Self::Api>,
+        config: RecoveryConfig<Self::Api>,
+    ) {
+        let guardian_count = config.guardian_addresses.len() + config.guardian_dids.len();
+        require!(
+            config.quorum >= 1 && config.quorum as usize <= guardian_count,
+            "Quorum must be between 1 and the number of guardians"
+        );
+
+        for (index, guardian) in config.guardian_addresses.iter().enumerate() {
+            require!(
+                !document.controllers.contains(&guardian),
+                "A controller cannot be a guardian"
+            );
+            let duplicate = config
+                .guardian_addresses
+                .iter()
+                .skip(index + 1)
+                .any(|other| *other == *guardian);
+            require!(!duplicate, "Duplicate guardian");
+        }
+        for (index, guardian_did) in config.guardian_dids.iter().enumerate() {
+            require!(*guardian_did != document.did, "A DID cannot guard itself");
+            require!(
+                !self.did_documents(&guardian_did).is_empty(),
+                "Guardian DID not found"
+            );
+            let duplicate = config
+                .guardian_dids
+                .iter()
+                .skip(index + 1)
+                .any(|other| *other == *guardian_did);
+            require!(!duplicate, "Duplicate guardian");
+        }
+
+        // A new guardian set replaces whatever recovery was in progress
+        self.recovery_request(&document.did).clear();
+        self.recovery_config(&document.did).set(&config);
+    }
+
+    // Records the caller's approval for every guardian entry it speaks for,
+    // either directly or as one of the controllers of a guardian DID
+    fn record_guardian_approval(
+        &self,
+        config: &RecoveryConfig<Self::Api>,
+        request: &mut RecoveryRequest<Self::Api>,
+        guardian: &ManagedAddress<Self::Api>,
+    ) -> bool {
+        let mut is_guardian = false;
+        let mut approved = false;
+
+        if config.guardian_addresses.contains(guardian) {
+            is_guardian = true;
+            if !request.approved_addresses.contains(guardian) {
+                request.approved_addresses.push(guardian.clone());
+                approved = true;
+            }
+        }
+
+        let controls_guardian_did = config.guardian_dids.iter().any(|guardian_did| {
+            let guardian_document = self.did_documents(&guardian_did).get();
+            !guardian_document.deactivated && guardian_document.controllers.contains(guardian)
+        });
+        if controls_guardian_did {
+            is_guardian = true;
+            if !request.guardian_did_approvers.contains(guardian) {
+                request.guardian_did_approvers.push(guardian.clone());
+                approved = true;
+            }
+        }
+
+        // A guardian DID approves once its own controller threshold is met
+        for guardian_did in config.guardian_dids.iter() {
+            if request.approved_dids.contains(&guardian_did) {
+                continue;
+            }
+            let guardian_document = self.did_documents(&guardian_did).get();
+            if guardian_document.deactivated {
+                continue;
+            }
+
+            let approvals = self.count_controller_approvals(&guardian_document, &request.guardian_did_approvers);
+            if approvals >= guardian_document.threshold.max(1) {
+                request.approved_dids.push((*guardian_did).clone());
+            }
+        }
+
+        require!(is_guardian, "Only a guardian can approve a recovery");
+        if request.quorum_reached.is_none()
+            && (request.approved_addresses.len() + request.approved_dids.len()) as u32 >= config.quorum
+        {
+            request.quorum_reached = Some(self.blockchain().get_block_timestamp());
+        }
+        approved
+    }
+
+    fn set_did_controllers(
+        &self,
+        document: &mut DidDocument<Self::Api>,
+        controllers: ManagedVec<Self::Api, ManagedAddress<Self::Api>>,
+        threshold: u32,
+    ) {
+        require!(!controllers.is_empty(), "At least one controller is required");
+        require!(
+            threshold >= 1 && threshold as usize <= controllers.len(),
+            "Threshold must be between 1 and the number of controllers"
+        );
+        for (index, controller) in controllers.iter().enumerate() {
+            let duplicate = controllers
+                .iter()
+                .skip(index + 1)
+                .any(|other| *other == *controller);
+            require!(!duplicate, "Duplicate controller");
+        }
+
+        for previous in document.controllers.iter() {
+            if controllers.contains(&previous) {
+                continue;
+            }
+            self.did_by_controller(&previous).swap_remove(&document.did);
+            if self.address_to_did(&previous).get() == document.did {
+                // Fall back to another DID the address still controls
+                match self.did_by_controller(&previous).iter().next() {
+                    Some(remaining) => self.address_to_did(&previous).set(&remaining),
+                    None => self.address_to_did(&previous).clear(),
+                }
+            }
+        }
+        for controller in controllers.iter() {
+            self.did_by_controller(&controller).insert(document.did.clone());
+            if self.address_to_did(&controller).is_empty() {
+                self.address_to_did(&controller).set(&document.did);
+            }
+        }
+
+        // A transfer offered by the previous controllers no longer applies
+        self.pending_did_transfer(&document.did).clear();
+
+        document.controllers = controllers;
+        document.threshold = threshold;
+    }
+
+    fn save_did_document(&self, document: &mut DidDocument<Self::Api>) {
+        document.updated = self.blockchain().get_block_timestamp();
+        document.version_id += 1;
+        self.did_documents(&document.did).set(&*document);
+        self.record_did_version(document);
+    }
+
+    // Appends an immutable snapshot chained to the hash of the previous one
+    fn record_did_version(&self, document: &DidDocument<Self::Api>) {
+        let versions = self.did_document_versions(&document.did);
+        let previous_hash = if versions.is_empty() {
+            ManagedBuffer::new()
+        } else {
+            let previous = versions.get(versions.len());
+            let encoded = self.serializer().top_encode_to_managed_buffer(&previous);
+            self.crypto().sha256(&encoded).as_managed_buffer().clone()
+        };
+
+        let version = DidDocumentVersion {
+            version_id: document.version_id,
+            timestamp: self.blockchain().get_block_timestamp(),
+            previous_hash,
+            document: document.clone(),
+        };
+        self.did_document_versions(&document.did).push(&version);
+    }
+
+    // Latest version recorded at or before `timestamp`
+    fn did_version_at(&self, did: &ManagedBuffer, timestamp: u64) -> Option<u64> {
+        let versions = self.did_document_versions(did);
+        let (mut low, mut high) = (1usize, versions.len());
+        let mut found = None;
+        while low <= high {
+            let middle = low + (high - low) / 2;
+            if versions.get(middle).timestamp <= timestamp {
+                found = Some(middle as u64);
+                low = middle + 1;
+            } else {
+                high = middle - 1;
+            }
+        }
+        found
+    }
+
+    // Fragment ids must be unique across verification methods, revoked keys and services
+    fn require_fragment_available(
+        &self,
+        document: &DidDocument<Self::Api>,
+        id: &ManagedBuffer<Self::Api>,
+    ) {
+        let fragment = split_did_url(id).1;
+        require!(!fragment.is_empty(), "Fragment id cannot be empty");
+
+        let in_use = document
+            .verification_methods
+            .iter()
+            .any(|method| split_did_url(&method.id).1 == fragment)
+            || document
+                .revoked_keys
+                .iter()
+                .any(|key| split_did_url(&key.method.id).1 == fragment)
+            || document
+                .service_endpoints
+                .iter()
+                .any(|service| split_did_url(&service.id).1 == fragment);
+        if in_use {
+            sc_panic!("Duplicate fragment id: {}", fragment);
+        }
+    }
+
+    fn verification_method_index(
+        &self,
+        document: &DidDocument<Self::Api>,
+        id: &ManagedBuffer<Self::Api>,
+    ) -> Option<usize> {
+        let fragment = split_did_url(id).1;
+        document
+            .verification_methods
+            .iter()
+            .position(|method| split_did_url(&method.id).1 == fragment)
+    }
+
+    fn service_index(
+        &self,
+        document: &DidDocument<Self::Api>,
+        id: &ManagedBuffer<Self::Api>,
+    ) -> Option<usize> {
+        let fragment = split_did_url(id).1;
+        document
+            .service_endpoints
+            .iter()
+            .position(|service| split_did_url(&service.id).1 == fragment)
+    }
+
+    // Keeps only the relationship entries that do not point at the given fragment
+    fn without_fragment(
+        &self,
+        ids: &ManagedVec<Self::Api, ManagedBuffer<Self::Api>>,
+        id: &ManagedBuffer<Self::Api>,
+    ) -> ManagedVec<Self::Api, ManagedBuffer<Self::Api>> {
+        let fragment = &split_did_url(id).1;
+        let mut result = ManagedVec::new();
+        for id in ids.iter() {
+            if split_did_url(&id).1 != *fragment {
+                result.push((*id).clone());
+            }
+        }
+        result
+    }
+
+    // Moves a verification method to the revoked keys so that proofs created
+    // before this point keep verifying
+    fn retire_verification_method(&self, document: &mut DidDocument<Self::Api>, index: usize) {
+        let method = document.verification_methods.get(index).clone();
+        document.verification_methods.remove(index);
+        document.revoked_keys.push(RevokedKey {
+            method,
+            revoked_at: self.blockchain().get_block_timestamp(),
+        });
+    }
+
+    // Replaces the relationship entries pointing at `old_id` with `new_id`
+    fn replace_fragment(
+        &self,
+        ids: &ManagedVec<Self::Api, ManagedBuffer<Self::Api>>,
+        old_id: &ManagedBuffer<Self::Api>,
+        new_id: &ManagedBuffer<Self::Api>,
+    ) -> ManagedVec<Self::Api, ManagedBuffer<Self::Api>> {
+        let fragment = &split_did_url(old_id).1;
+        let mut result = ManagedVec::new();
+        for id in ids.iter() {
+            if split_did_url(&id).1 == *fragment {
+                result.push(new_id.clone());
+            } else {
+                result.push((*id).clone());
+            }
+        }
+        result
+    }
+
+    fn require_valid_key_commitment(&self, commitment: &ManagedBuffer<Self::Api>) {
+        require!(commitment.len() == 32, "Key commitment must be a SHA-256 hash");
+    }
+
+    // Finds the key that was valid for a proof created at `timestamp`,
+    // including keys that were rotated out afterwards
+    fn find_key_valid_at(
+        &self,
+        document: &DidDocument<Self::Api>,
+        fragment: &ManagedBuffer<Self::Api>,
+        timestamp: u64,
+    ) -> Option<VerificationMethod<Self::Api>> {
+        if let Some(method) = self.find_verification_method(document, fragment) {
+            return Some(method);
+        }
+
+        document
+            .revoked_keys
+            .iter()
+            .find(|key| split_did_url(&key.method.id).1 == *fragment && timestamp <= key.revoked_at)
+            .map(|key| key.method.clone())
+    }
+
+    fn require_known_methods(
+        &self,
+        document: &DidDocument<Self::Api>,
+        ids: &ManagedVec<Self::Api, ManagedBuffer<Self::Api>>,
+    ) {
+        for id in ids.iter() {
+            let fragment = split_did_url(&id).1;
+            if self.find_verification_method(document, &fragment).is_none() {
+                sc_panic!("Unknown verification method: {}", fragment);
+            }
+        }
+    }
+
+    // While a next key is committed, new keys can only reach a relationship
+    // through `rotateKey`
+    fn require_no_uncommitted_keys(
+        &self,
+        current: &ManagedVec<Self::Api, ManagedBuffer<Self::Api>>,
+        proposed: &ManagedVec<Self::Api, ManagedBuffer<Self::Api>>,
+    ) {
+        for id in proposed.iter() {
+            let fragment = split_did_url(&id).1;
+            let known = current.iter().any(|current_id| split_did_url(&current_id).1 == fragment);
+            if !known {
+                sc_panic!("Key {} must be introduced through rotateKey", fragment);
+            }
+        }
+    }
+
+    fn verify_delegation(
+        &self,
+        delegation_id: u64,
+        credential_type: &ManagedVec<Self::Api, ManagedBuffer<Self::Api>>,
+    ) -> bool {
+        if let Some(error) = self.delegation_error(delegation_id, credential_type) {
+            sc_panic!(error);
+        }
+
+        true
+    }
+
+    fn require_own_delegation(&self, delegation_id: u64) -> Delegation<Self::Api> {
+        require!(
+            !self.delegations(&delegation_id).is_empty(),
+            "Delegation not found"
+        );
+        let delegation = self.delegations(&delegation_id).get();
+        require!(
+            delegation.delegator == self.blockchain().get_caller(),
+            "Only the delegator can manage this delegation"
+        );
+        require!(!delegation.revoked, "Delegation is revoked");
+        delegation
+    }
+
+    fn require_valid_delegation_terms(
+        &self,
+        credential_types: &ManagedVec<Self::Api, ManagedBuffer<Self::Api>>,
+        valid_until: u64,
+        limits: &DelegationLimits<Self::Api>,
+        depth: u32,
+    ) {
+        require!(!credential_types.is_empty(), "Delegation must allow at least one credential type");
+        require!(
+            valid_until > self.blockchain().get_block_timestamp(),
+            "Delegation must end in the future"
+        );
+        require!(
+            limits.max_credentials != Some(0),
+            "Credential limit must be greater than zero"
+        );
+        require!(
+            depth + limits.max_sub_delegation_depth <= self.max_delegation_depth().get(),
+            "Delegation chain would exceed the maximum depth"
+        );
+    }
+
+    // A sub-delegation can only narrow the rights of its parent
+    fn require_within_parent_delegation(
+        &self,
+        parent: &Delegation<Self::Api>,
+        credential_types: &ManagedVec<Self::Api, ManagedBuffer<Self::Api>>,
+        valid_until: u64,
+        limits: &DelegationLimits<Self::Api>,
+    ) {
+        require!(
+            credential_types
+                .iter()
+                .all(|type_| parent.credential_types.contains(&*type_)),
+            "Credential types must be a subset of the parent delegation"
+        );
+        require!(
+            valid_until <= parent.valid_until,
+            "Sub-delegation cannot outlive the parent delegation"
+        );
+        require!(
+            limits.max_sub_delegation_depth < parent.limits.max_sub_delegation_depth,
+            "Parent delegation does not allow further sub-delegation"
+        );
+        if let Some(parent_max) = parent.limits.max_credentials {
+            require!(
+                limits.max_credentials.is_some_and(|max| max <= parent_max),
+                "Credential limit cannot exceed the parent delegation"
+            );
+        }
+        if !parent.limits.allowed_schema_ids.is_empty() {
+            require!(
+                !limits.allowed_schema_ids.is_empty()
+                    && limits
+                        .allowed_schema_ids
+                        .iter()
+                        .all(|id| parent.limits.allowed_schema_ids.contains(&*id)),
+                "Allowed schemas must be a subset of the parent delegation"
+            );
+        }
+        if !parent.limits.allowed_holders.is_empty() {
+            require!(
+                !limits.allowed_holders.is_empty()
+                    && limits
+                        .allowed_holders
+                        .iter()
+                        .all(|holder| parent.limits.allowed_holders.contains(&*holder)),
+                "Allowed holders must be a subset of the parent delegation"
+            );
+        }
+    }
+
+    // Delegation ids from the root delegation down to the given one
+    fn delegation_chain(&self, delegation_id: u64) -> ManagedVec<Self::Api, u64> {
+        let mut chain = ManagedVec::<Self::Api, u64>::new();
+        let mut current = Some(delegation_id);
+        while let Some(id) = current {
+            if self.delegations(&id).is_empty() {
+                break;
+            }
+            chain.push(id);
+            current = self.delegations(&id).get().parent_id;
+        }
+
+        let mut root_first = ManagedVec::new();
+        for index in (0..chain.len()).rev() {
+            root_first.push(chain.get(index));
+        }
+        root_first
+    }
+
+    fn require_within_delegation_limits(
+        &self,
+        delegation: &Delegation<Self::Api>,
+        schema_id: &ManagedBuffer<Self::Api>,
+        holder: &ManagedAddress<Self::Api>,
+    ) {
+        let limits = &delegation.limits;
+        if let Some(max_credentials) = limits.max_credentials {
+            require!(
+                delegation.issued_count < max_credentials,
+                "Delegation credential limit reached"
+            );
+        }
+        require!(
+            limits.allowed_schema_ids.is_empty() || limits.allowed_schema_ids.contains(schema_id),
+            "Schema not allowed under this delegation"
+        );
+        require!(
+            limits.allowed_holders.is_empty() || limits.allowed_holders.contains(holder),
+            "Holder not allowed under this delegation"
+        );
+    }
+
+    // Returns why a delegation does not cover the given credential types, if it does not.
+    // Every ancestor must still be valid, so revoking a parent invalidates its subtree.
+    fn delegation_error(
+        &self,
+        delegation_id: u64,
+        credential_type: &ManagedVec<Self::Api, ManagedBuffer<Self::Api>>,
+    ) -> Option<ManagedBuffer<Self::Api>> {
+        let mut current = Some(delegation_id);
+        while let Some(id) = current {
+            if self.delegations(&id).is_empty() {
+                return Some(sc_format!("Delegation {} not found", id));
+            }
+            let delegation = self.delegations(&id).get();
+            if delegation.depth > self.max_delegation_depth().get() {
+                return Some(sc_format!(
+                    "Delegation {} exceeds the maximum chain depth",
+                    id
+                ));
+            }
+            if let Some(error) = self.delegation_link_error(&delegation, credential_type) {
+                return Some(error);
+            }
+            current = delegation.parent_id;
+        }
+
+        None
+    }
+
+    // A credential stays backed by its delegation chain unless a link has since
+    // been revoked; expiry is judged at the time the credential was issued
+    fn issued_delegation_error(
+        &self,
+        delegation_id: u64,
+        issued_at: u64,
+    ) -> Option<ManagedBuffer<Self::Api>> {
+        let mut current = Some(delegation_id);
+        while let Some(id) = current {
+            if self.delegations(&id).is_empty() {
+                return Some(sc_format!("Delegation {} not found", id));
+            }
+            let delegation = self.delegations(&id).get();
+            if delegation.revoked {
+                return Some(sc_format!("Delegation {} is revoked", id));
+            }
+            if issued_at > delegation.valid_until {
+                return Some(sc_format!("Delegation {} had expired at issuance", id));
+            }
+            current = delegation.parent_id;
+        }
+
+        None
+    }
+
+    fn delegation_link_error(
+        &self,
+        delegation: &Delegation<Self::Api>,
+        credential_type: &ManagedVec<Self::Api, ManagedBuffer<Self::Api>>,
+    ) -> Option<ManagedBuffer<Self::Api>> {
+        let delegation_id = delegation.id;
+
+        // Check if delegation is revoked
+        if delegation.revoked {
+            return Some(sc_format!("Delegation {} is revoked", delegation_id));
+        }
+
+        // Check if delegation is expired
+        let current_timestamp = self.blockchain().get_block_timestamp();
+        if current_timestamp > delegation.valid_until {
+            return Some(sc_format!("Delegation {} has expired", delegation_id));
+        }
+
+        // Check if credential type is allowed under the delegation
+        let credential_type_match = credential_type
+            .iter()
+            .all(|type_| delegation.credential_types.contains(&*type_));
+
+        if !credential_type_match {
+
+            let joined_types = join_buffers(&delegation.credential_types);
+
+            return Some(sc_format!(
+                "Delegation {} only permits issuance of credential type: {}",
+                delegation_id,
+                joined_types
+            ));
+        }
+
+        None
+    }
+
+    fn did_resolution_error(&self, error: &str) -> DidResolutionResult<Self::Api> {
+        DidResolutionResult {
+            did_document: None,
+            did_document_metadata: None,
+            did_resolution_metadata: DidResolutionMetadata {
+                error: Some(ManagedBuffer::from(error)),
+            },
+            dereferenced_method: None,
+            dereferenced_service: None,
+        }
+    }
+
+    // Resolves `did:multiversx:<id>[?versionTime=<time>][#<fragment>]`. Legacy
+    // `did:multiversx<id>` DIDs resolve under both spellings.
+    fn resolve_did_url(&self, did_url: &ManagedBuffer) -> DidResolutionResult<Self::Api> {
+        let url = did_url.to_boxed_bytes();
+        let url = url.as_slice();
+
+        let (rest, fragment) = match url.iter().position(|&b| b == b'#') {
+            Some(pos) => (&url[..pos], Some(&url[pos + 1..])),
+            None => (url, None),
+        };
+        let (did, query) = match rest.iter().position(|&b| b == b'?') {
+            Some(pos) => (&rest[..pos], Some(&rest[pos + 1..])),
+            None => (rest, None),
+        };
+
+        let id = match did
+            .strip_prefix(DID_PREFIX)
+            .or_else(|| did.strip_prefix(LEGACY_DID_PREFIX))
+        {
+            Some(id) if !id.is_empty() && id.iter().all(u8::is_ascii_alphanumeric) => id,
+            _ => return self.did_resolution_error("invalidDid"),
+        };
+
+        let mut version_time = None;
+        let mut version_id = None;
+        if let Some(query) = query {
+            for param in query.split(|&b| b == b'&') {
+                if let Some(value) = param.strip_prefix(b"versionTime=") {
+                    match parse_version_time(value) {
+                        Some(time) => version_time = Some(time),
+                        None => return self.did_resolution_error("invalidDidUrl"),
+                    }
+                } else if let Some(value) = param.strip_prefix(b"versionId=") {
+                    match parse_digits(value) {
+                        Some(id) => version_id = Some(id as u64),
+                        None => return self.did_resolution_error("invalidDidUrl"),
+                    }
+                }
+            }
+        }
+
+        let mut did = ManagedBuffer::new_from_bytes(DID_PREFIX);
+        did.append_bytes(id);
+        if self.did_documents(&did).is_empty() {
+            did = self.legacy_did(&did);
+        }
+        if self.did_documents(&did).is_empty() {
+            return self.did_resolution_error("notFound");
+        }
+
+        let versions = self.did_document_versions(&did);
+        let version_id = match (version_id, version_time) {
+            (Some(id), _) => id,
+            (None, Some(time)) => match self.did_version_at(&did, time) {
+                Some(id) => id,
+                None => return self.did_resolution_error("notFound"),
+            },
+            (None, None) => versions.len() as u64,
+        };
+        if version_id == 0 || version_id > versions.len() as u64 {
+            return self.did_resolution_error("notFound");
+        }
+
+        let document = versions.get(version_id as usize).document;
+        let next_update = if version_id < versions.len() as u64 {
+            Some(versions.get(version_id as usize + 1).timestamp)
+        } else {
+            None
+        };
+
+        let mut dereferenced_method = None;
+        let mut dereferenced_service = None;
+        if let Some(fragment) = fragment {
+            let fragment = ManagedBuffer::new_from_bytes(fragment);
+            dereferenced_method = self.find_verification_method(&document, &fragment);
+            if dereferenced_method.is_none() {
+                dereferenced_service = self
+                    .service_index(&document, &fragment)
+                    .map(|index| document.service_endpoints.get(index).clone());
+            }
+            if dereferenced_method.is_none() && dereferenced_service.is_none() {
+                return self.did_resolution_error("notFound");
+            }
+        }
+
+        let error = if document.deactivated {
+            Some(ManagedBuffer::from("deactivated"))
+        } else {
+            None
+        };
+
+        DidResolutionResult {
+            did_document_metadata: Some(DidDocumentMetadata {
+                created: document.created,
+                updated: document.updated,
+                deactivated: document.deactivated,
+                version_id: document.version_id,
+                next_update,
+                next_version_id: next_update.map(|_| version_id + 1),
+            }),
+            did_document: Some(document),
+            did_resolution_metadata: DidResolutionMetadata { error },
+            dereferenced_method,
+            dereferenced_service,
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use multiversx_sc_scenario::DebugApi;
+
+    type Buffer = ManagedBuffer<DebugApi>;
+
+    fn contract() -> crate::ContractObj<DebugApi> {
+        crate::contract_obj::<DebugApi>()
+    }
+
+    fn sha256(bytes: &[u8]) -> Buffer {
+        contract()
+            .crypto()
+            .sha256(Buffer::new_from_bytes(bytes))
+            .as_managed_buffer()
+            .clone()
+    }
+
+    fn leaf(value: u8) -> Buffer {
+        contract().claim_leaf_hash(
+            &Buffer::from(b"attribute"),
+            &Buffer::new_from_bytes(&[value]),
+            &Buffer::from(b"salt"),
+        )
+    }
+
+    fn leaves(count: u8) -> Vec<Buffer> {
+        (0..count).map(leaf).collect()
+    }
+
+    fn root(leaves: &[Buffer]) -> Buffer {
+        contract().merkle_root(&leaves.iter().cloned().collect())
+    }
+
+    fn parent(left: &Buffer, right: &Buffer) -> Buffer {
+        contract().merkle_parent(left, right)
+    }
+
+    fn attribute(name: &[u8], disclosure: &[u8]) -> SchemaAttribute<DebugApi> {
+        SchemaAttribute {
+            name: Buffer::new_from_bytes(name),
+            attribute_type: Buffer::new_from_bytes(disclosure),
+            required: false,
+            data_type: Buffer::from(b"string"),
+            constraints: AttributeConstraints {
+                min_length: 0,
+                max_length: 0,
+                pattern: Buffer::new(),
+                allowed_values: ManagedVec::new(),
+                minimum: Buffer::new(),
+                maximum: Buffer::new(),
+                max_decimals: 0,
+                item_type: Buffer::new(),
+                min_items: 0,
+                max_items: 0,
+            },
+        }
+    }
+
+    fn claim(attribute: &[u8], value: &[u8], hash: Buffer) -> Claim<DebugApi> {
+        Claim {
+            attribute: Buffer::new_from_bytes(attribute),
+            value: Buffer::new_from_bytes(value),
+            salt: if value.is_empty() { Buffer::new() } else { Buffer::from(b"salt") },
+            hash,
+        }
+    }
+
+    #[test]
+    fn leaf_hash_is_domain_separated() {
+        DebugApi::dummy();
+        let leaf = contract().claim_leaf_hash(&Buffer::from(b"a"), &Buffer::from(b"bc"), &Buffer::from(b"d"));
+        let expected = sha256(&[
+            0x00, 0, 0, 0, 1, b'a', 0, 0, 0, 2, b'b', b'c', 0, 0, 0, 1, b'd',
+        ]);
+        assert_eq!(leaf, expected);
+    }
+
+    #[test]
+    fn parent_hash_sorts_the_pair() {
+        DebugApi::dummy();
+        let (a, b) = (leaf(0), leaf(1));
+        let (low, high) = if a.to_boxed_bytes().as_slice() <= b.to_boxed_bytes().as_slice() {
+            (&a, &b)
+        } else {
+            (&b, &a)
+        };
+        let mut node = Buffer::new_from_bytes(&[0x01]);
+        node.append(low);
+        node.append(high);
+        let expected = sha256(node.to_boxed_bytes().as_slice());
+
+        assert_eq!(parent(&a, &b), expected);
+        assert_eq!(parent(&b, &a), expected);
+    }
+
+    #[test]
+    fn roots_carry_odd_nodes_up() {
+        DebugApi::dummy();
+        assert_eq!(root(&[]), sha256(b""));
+
+        let one = leaves(1);
+        assert_eq!(root(&one), one[0]);
+
+        let two = leaves(2);
+        assert_eq!(root(&two), parent(&two[0], &two[1]));
+
+        let three = leaves(3);
+        assert_eq!(root(&three), parent(&parent(&three[0], &three[1]), &three[2]));
+
+        let five = leaves(5);
+        let top = parent(&parent(&five[0], &five[1]), &parent(&five[2], &five[3]));
+        assert_eq!(root(&five), parent(&top, &five[4]));
+    }
+
+    #[test]
+    fn inclusion_proofs_verify_only_the_committed_leaf() {
+        DebugApi::dummy();
+        let sc = contract();
+        let five = leaves(5);
+        let root = root(&five);
+        let proof = [five[3].clone(), parent(&five[0], &five[1]), five[4].clone()];
+        let last_proof = [parent(&parent(&five[0], &five[1]), &parent(&five[2], &five[3]))];
+
+        assert!(sc.verify_merkle_proof(five[2].clone(), proof.iter().cloned(), &root));
+        assert!(sc.verify_merkle_proof(five[4].clone(), last_proof.iter().cloned(), &root));
+
+        // A different value under the same attribute and salt
+        assert!(!sc.verify_merkle_proof(leaf(9), proof.iter().cloned(), &root));
+
+        // Proofs that are too short or too long
+        assert!(!sc.verify_merkle_proof(five[2].clone(), proof[..2].iter().cloned(), &root));
+        let longer = proof.iter().cloned().chain([five[0].clone()]);
+        assert!(!sc.verify_merkle_proof(five[2].clone(), longer, &root));
+    }
+
+    #[test]
+    fn sealing_hashes_claims_and_leaves_private_ones_out_of_the_tree() {
+        DebugApi::dummy();
+        let sc = contract();
+        let mut attributes = ManagedVec::new();
+        attributes.push(attribute(b"name", b"public"));
+        attributes.push(attribute(b"birthDate", b"selectively-disclosable"));
+        attributes.push(attribute(b"nationalId", b"private"));
+        let schema = CredentialSchema {
+            id: Buffer::from(b"schema"),
+            type_: Buffer::from(b"Identity"),
+            attributes,
+            issuer: ManagedAddress::zero(),
+            issuer_did: Buffer::new(),
+            version: Buffer::from(b"1"),
+            strict: true,
+            additional_properties: false,
+            created: 0,
+            deprecated_at: None,
+            superseded_by: None,
+        };
+
+        let birth_date = sha256(b"birth date commitment");
+        let national_id = sha256(b"national id commitment");
+        let mut data = ManagedVec::new();
+        data.push(claim(b"name", b"Alice", Buffer::new()));
+        data.push(claim(b"birthDate", b"", birth_date.clone()));
+        data.push(claim(b"nationalId", b"", national_id.clone()));
+        let mut claims = Claims {
+            data,
+            merkle_root: Buffer::new(),
+            hash_only: false,
+        };
+        sc.validate_claims_against_schema(&claims, &schema);
+        sc.seal_claims(&mut claims, &schema);
+
+        let name = sc.claim_leaf_hash(&Buffer::from(b"name"), &Buffer::from(b"Alice"), &Buffer::from(b"salt"));
+        assert_eq!(claims.data.get(0).hash, name);
+        assert_eq!(claims.data.get(2).hash, national_id);
+        assert!(claims.data.get(2).value.is_empty());
+        assert_eq!(claims.merkle_root, parent(&name, &birth_date));
+
+        assert!(sc.is_revealable_claim(&schema, &Buffer::from(b"birthDate")));
+        assert!(!sc.is_revealable_claim(&schema, &Buffer::from(b"nationalId")));
+    }
+
+    fn base58(encoded: &[u8]) -> Option<Vec<u8>> {
+        let mut output = [0u8; MAX_DECODED_KEY_LEN];
+        let len = decode_base58(encoded, &mut output)?;
+        Some(output[..len].to_vec())
+    }
+
+    fn multibase(encoded: &[u8]) -> Option<(KeyCodec, Vec<u8>)> {
+        decode_public_key_multibase(&Buffer::new_from_bytes(encoded))
+            .map(|(codec, key)| (codec, key.to_boxed_bytes().as_slice().to_vec()))
+    }
+
+    fn hex_key(header: &[u8], key: &[u8]) -> Vec<u8> {
+        let mut encoded = b"f".to_vec();
+        for byte in header.iter().chain(key) {
+            encoded.extend_from_slice(format_hex(*byte).as_slice());
+        }
+        encoded
+    }
+
+    fn format_hex(byte: u8) -> [u8; 2] {
+        const DIGITS: &[u8] = b"0123456789abcdef";
+        [DIGITS[(byte >> 4) as usize], DIGITS[(byte & 0x0f) as usize]]
+    }
+
+    #[test]
+    fn base58_decoding() {
+        assert_eq!(base58(b"2NEpo7TZRRrLZSi2U").unwrap(), b"Hello World!".to_vec());
+        assert_eq!(base58(b"").unwrap(), Vec::<u8>::new());
+        assert_eq!(base58(b"1").unwrap(), [0u8].to_vec());
+        assert_eq!(base58(b"1112").unwrap(), [0u8, 0, 0, 1].to_vec());
+        assert_eq!(base58(b"5R").unwrap(), [0x01u8, 0x00].to_vec());
+
+        // '0', 'O', 'I' and 'l' are not part of the alphabet
+        assert!(base58(b"10").is_none());
+        assert!(base58(b"O").is_none());
+        assert!(base58(b"I").is_none());
+        assert!(base58(b"l").is_none());
+
+        // Longer than MAX_DECODED_KEY_LEN bytes once decoded
+        assert!(base58(&[b'z'; 200]).is_none());
+        assert!(base58(&[b'1'; MAX_DECODED_KEY_LEN + 1]).is_none());
+        assert_eq!(base58(&[b'1'; MAX_DECODED_KEY_LEN]).unwrap().len(), MAX_DECODED_KEY_LEN);
+    }
+
+    #[test]
+    fn multibase_public_keys() {
+        DebugApi::dummy();
+
+        let (codec, key) = multibase(b"z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK").unwrap();
+        assert!(codec == KeyCodec::Ed25519);
+        assert_eq!(key.len(), 32);
+
+        let ed25519 = [7u8; 32];
+        let (codec, key) = multibase(&hex_key(&ED25519_PUB_HEADER, &ed25519)).unwrap();
+        assert!(codec == KeyCodec::Ed25519);
+        assert_eq!(key, ed25519.to_vec());
+
+        let secp256k1 = [2u8; 33];
+        let (codec, key) = multibase(&hex_key(&SECP256K1_PUB_HEADER, &secp256k1)).unwrap();
+        assert!(codec == KeyCodec::Secp256k1);
+        assert_eq!(key, secp256k1.to_vec());
+
+        let bls = [9u8; 96];
+        let (codec, key) = multibase(&hex_key(&BLS12381_G2_PUB_HEADER, &bls)).unwrap();
+        assert!(codec == KeyCodec::Bls12381G2);
+        assert_eq!(key, bls.to_vec());
+
+        // Raw keys and headers with the wrong key length keep every byte
+        let (codec, key) = multibase(&hex_key(&[], &ed25519)).unwrap();
+        assert!(codec == KeyCodec::Unknown);
+        assert_eq!(key, ed25519.to_vec());
+        let (codec, key) = multibase(&hex_key(&ED25519_PUB_HEADER, &ed25519[..31])).unwrap();
+        assert!(codec == KeyCodec::Unknown);
+        assert_eq!(key.len(), 33);
+
+        assert!(multibase(b"").is_none());
+        assert!(multibase(b"m7QE=").is_none());
+        assert!(multibase(b"fed0").is_none());
+        assert!(multibase(b"fzz").is_none());
+        assert!(multibase(b"z0").is_none());
+        assert!(multibase(&hex_key(&[], &[0u8; MAX_DECODED_KEY_LEN + 1])).is_none());
+    }
+}
@@ -0,0 +1,462 @@
+use multiversx_sc::imports::*;
+
+use crate::{
+    schema_validation::*,
+    structures::{AttributeConstraints, CredentialSchema},
+};
+
+// Renders a credential schema as a JSON Schema (draft 2020-12) document for the
+// `credentialSchema` property of a W3C Verifiable Credential. Claims are
+// described under `credentialSubject`, the way VC JSON Schemas expect them.
+
+const JSON_SCHEMA_DIALECT: &[u8] = b"https://json-schema.org/draft/2020-12/schema";
+const SCHEMA_ID_PREFIX: &[u8] = b"urn:multiversx:schema:";
+const HEX_DIGITS: &[u8] = b"0123456789abcdef";
+
+struct JsonWriter<M: ManagedTypeApi> {
+    output: ManagedBuffer<M>,
+    needs_comma: bool,
+}
+
+impl<M: ManagedTypeApi> JsonWriter<M> {
+    fn new() -> Self {
+        JsonWriter {
+            output: ManagedBuffer::new(),
+            needs_comma: false,
+        }
+    }
+
+    fn separate(&mut self) {
+        if self.needs_comma {
+            self.output.append_bytes(b",");
+        }
+    }
+
+    fn begin(&mut self, bracket: &[u8]) {
+        self.separate();
+        self.output.append_bytes(bracket);
+        self.needs_comma = false;
+    }
+
+    fn end(&mut self, bracket: &[u8]) {
+        self.output.append_bytes(bracket);
+        self.needs_comma = true;
+    }
+
+    fn key(&mut self, key: &[u8]) {
+        self.string(key);
+        self.output.append_bytes(b":");
+        self.needs_comma = false;
+    }
+
+    fn raw(&mut self, bytes: &[u8]) {
+        self.separate();
+        self.output.append_bytes(bytes);
+        self.needs_comma = true;
+    }
+
+    fn string(&mut self, bytes: &[u8]) {
+        self.separate();
+        self.output.append_bytes(b"\"");
+        let mut start = 0;
+        for (index, &byte) in bytes.iter().enumerate() {
+            if byte != b'"' && byte != b'\\' && byte >= 0x20 {
+                continue;
+            }
+            self.output.append_bytes(&bytes[start..index]);
+            match byte {
+                b'"' => self.output.append_bytes(b"\\\""),
+                b'\\' => self.output.append_bytes(b"\\\\"),
+                _ => self.output.append_bytes(&[
+                    b'\\',
+                    b'u',
+                    b'0',
+                    b'0',
+                    HEX_DIGITS[(byte >> 4) as usize],
+                    HEX_DIGITS[(byte & 0x0f) as usize],
+                ]),
+            }
+            start = index + 1;
+        }
+        self.output.append_bytes(&bytes[start..]);
+        self.output.append_bytes(b"\"");
+        self.needs_comma = true;
+    }
+
+    fn unsigned(&mut self, mut value: u128) {
+        let mut digits = [0u8; 40];
+        let mut start = digits.len();
+        loop {
+            start -= 1;
+            digits[start] = b'0' + (value % 10) as u8;
+            value /= 10;
+            if value == 0 {
+                break;
+            }
+        }
+        self.raw(&digits[start..]);
+    }
+
+    // Writes a value scaled by 10^18 as a plain JSON number
+    fn decimal(&mut self, scaled: i128) {
+        if scaled < 0 {
+            self.separate();
+            self.output.append_bytes(b"-");
+            self.needs_comma = false;
+        }
+        let magnitude = scaled.unsigned_abs();
+        let scale = DECIMAL_SCALE as u128;
+        self.unsigned(magnitude / scale);
+
+        let mut fraction = magnitude % scale;
+        if fraction == 0 {
+            return;
+        }
+        let mut digits = [b'0'; MAX_DECIMALS as usize];
+        for digit in digits.iter_mut().rev() {
+            *digit = b'0' + (fraction % 10) as u8;
+            fraction /= 10;
+        }
+        let len = digits.iter().rposition(|&digit| digit != b'0').map_or(0, |last| last + 1);
+        self.output.append_bytes(b".");
+        self.output.append_bytes(&digits[..len]);
+    }
+
+    // Numbers and booleans are written as JSON literals, everything else as strings
+    fn value(&mut self, data_type: DataType, bytes: &[u8]) {
+        match data_type {
+            DataType::Integer => match parse_integer(bytes) {
+                Some(value) => self.decimal(value as i128 * DECIMAL_SCALE),
+                None => self.string(bytes),
+            },
+            DataType::Decimal => match parse_decimal(bytes) {
+                Some((value, _)) => self.decimal(value),
+                None => self.string(bytes),
+            },
+            DataType::Boolean => self.raw(bytes),
+            _ => self.string(bytes),
+        }
+    }
+
+    fn finish(self) -> ManagedBuffer<M> {
+        self.output
+    }
+}
+
+fn json_type(data_type: DataType) -> &'static [u8] {
+    match data_type {
+        DataType::Integer => b"integer",
+        DataType::Decimal => b"number",
+        DataType::Boolean => b"boolean",
+        DataType::List => b"array",
+        _ => b"string",
+    }
+}
+
+fn json_format(data_type: DataType) -> Option<&'static [u8]> {
+    match data_type {
+        DataType::Date => Some(b"date"),
+        DataType::DateTime => Some(b"date-time"),
+        DataType::Email => Some(b"email"),
+        DataType::Uri => Some(b"uri"),
+        _ => None,
+    }
+}
+
+fn data_type_of<M: ManagedTypeApi>(name: &ManagedBuffer<M>) -> DataType {
+    DataType::parse(name.to_boxed_bytes().as_slice()).unwrap_or(DataType::String)
+}
+
+fn write_value_schema<M: ManagedTypeApi>(
+    writer: &mut JsonWriter<M>,
+    data_type: DataType,
+    constraints: &AttributeConstraints<M>,
+) {
+    writer.begin(b"{");
+    writer.key(b"type");
+    writer.string(json_type(data_type));
+    if let Some(format) = json_format(data_type) {
+        writer.key(b"format");
+        writer.string(format);
+    }
+
+    if !constraints.allowed_values.is_empty() {
+        writer.key(b"enum");
+        writer.begin(b"[");
+        for value in constraints.allowed_values.iter() {
+            writer.value(data_type, value.to_boxed_bytes().as_slice());
+        }
+        writer.end(b"]");
+    }
+
+    if json_type(data_type) == b"string" {
+        if constraints.min_length > 0 {
+            writer.key(b"minLength");
+            writer.unsigned(constraints.min_length as u128);
+        }
+        if constraints.max_length > 0 {
+            writer.key(b"maxLength");
+            writer.unsigned(constraints.max_length as u128);
+        }
+        if !constraints.pattern.is_empty() {
+            // Schema patterns always match the whole value
+            let pattern = constraints.pattern.to_boxed_bytes();
+            let mut anchored = ManagedBuffer::<M>::new_from_bytes(b"^");
+            anchored.append_bytes(strip_anchors(pattern.as_slice()));
+            anchored.append_bytes(b"$");
+            writer.key(b"pattern");
+            writer.string(anchored.to_boxed_bytes().as_slice());
+        }
+    }
+
+    // 2020-12 has no keyword for date bounds, so they are only enforced on-chain
+    let has_bounds = !matches!(data_type, DataType::Date | DataType::DateTime);
+    if has_bounds && !constraints.minimum.is_empty() {
+        writer.key(b"minimum");
+        writer.value(data_type, constraints.minimum.to_boxed_bytes().as_slice());
+    }
+    if has_bounds && !constraints.maximum.is_empty() {
+        writer.key(b"maximum");
+        writer.value(data_type, constraints.maximum.to_boxed_bytes().as_slice());
+    }
+    if constraints.max_decimals > 0 {
+        writer.key(b"multipleOf");
+        writer.decimal(DECIMAL_SCALE / 10i128.pow(constraints.max_decimals));
+    }
+    writer.end(b"}");
+}
+
+pub fn render_json_schema<M: ManagedTypeApi>(schema: &CredentialSchema<M>) -> ManagedBuffer<M> {
+    let mut writer = JsonWriter::new();
+    writer.begin(b"{");
+    writer.key(b"$schema");
+    writer.string(JSON_SCHEMA_DIALECT);
+    let mut id = ManagedBuffer::<M>::new_from_bytes(SCHEMA_ID_PREFIX);
+    id.append(&schema.id);
+    writer.key(b"$id");
+    writer.string(id.to_boxed_bytes().as_slice());
+    writer.key(b"title");
+    writer.string(schema.type_.to_boxed_bytes().as_slice());
+
+    let mut description = ManagedBuffer::<M>::new_from_bytes(b"Version ");
+    description.append(&schema.version);
+    description.append_bytes(b" issued by ");
+    description.append(&schema.issuer_did);
+    writer.key(b"description");
+    writer.string(description.to_boxed_bytes().as_slice());
+    if schema.deprecated_at.is_some() {
+        writer.key(b"deprecated");
+        writer.raw(b"true");
+    }
+    writer.key(b"type");
+    writer.string(b"object");
+
+    writer.key(b"properties");
+    writer.begin(b"{");
+    writer.key(b"credentialSubject");
+    writer.begin(b"{");
+    writer.key(b"type");
+    writer.string(b"object");
+
+    writer.key(b"properties");
+    writer.begin(b"{");
+    for attribute in schema.attributes.iter() {
+        writer.key(attribute.name.to_boxed_bytes().as_slice());
+        let data_type = data_type_of(&attribute.data_type);
+        if data_type != DataType::List {
+            write_value_schema(&mut writer, data_type, &attribute.constraints);
+            continue;
+        }
+
+        let constraints = &attribute.constraints;
+        writer.begin(b"{");
+        writer.key(b"type");
+        writer.string(b"array");
+        writer.key(b"items");
+        write_value_schema(&mut writer, data_type_of(&constraints.item_type), constraints);
+        if constraints.min_items > 0 {
+            writer.key(b"minItems");
+            writer.unsigned(constraints.min_items as u128);
+        }
+        if constraints.max_items > 0 {
+            writer.key(b"maxItems");
+            writer.unsigned(constraints.max_items as u128);
+        }
+        writer.end(b"}");
+    }
+    writer.end(b"}");
+
+    writer.key(b"required");
+    writer.begin(b"[");
+    for attribute in schema.attributes.iter().filter(|attribute| attribute.required) {
+        writer.string(attribute.name.to_boxed_bytes().as_slice());
+    }
+    writer.end(b"]");
+    if !schema.additional_properties {
+        writer.key(b"additionalProperties");
+        writer.raw(b"false");
+    }
+    writer.end(b"}");
+    writer.end(b"}");
+
+    writer.key(b"required");
+    writer.begin(b"[");
+    writer.string(b"credentialSubject");
+    writer.end(b"]");
+    writer.end(b"}");
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structures::SchemaAttribute;
+    use multiversx_sc_scenario::api::StaticApi;
+
+    type Buffer = ManagedBuffer<StaticApi>;
+
+    fn rendered(writer: JsonWriter<StaticApi>) -> Vec<u8> {
+        writer.finish().to_boxed_bytes().as_slice().to_vec()
+    }
+
+    fn constraints() -> AttributeConstraints<StaticApi> {
+        AttributeConstraints {
+            min_length: 0,
+            max_length: 0,
+            pattern: Buffer::new(),
+            allowed_values: ManagedVec::new(),
+            minimum: Buffer::new(),
+            maximum: Buffer::new(),
+            max_decimals: 0,
+            item_type: Buffer::new(),
+            min_items: 0,
+            max_items: 0,
+        }
+    }
+
+    fn attribute(
+        name: &[u8],
+        data_type: &[u8],
+        required: bool,
+        constraints: AttributeConstraints<StaticApi>,
+    ) -> SchemaAttribute<StaticApi> {
+        SchemaAttribute {
+            name: Buffer::new_from_bytes(name),
+            attribute_type: Buffer::from(b"public"),
+            required,
+            data_type: Buffer::new_from_bytes(data_type),
+            constraints,
+        }
+    }
+
+    fn schema(attributes: ManagedVec<StaticApi, SchemaAttribute<StaticApi>>) -> CredentialSchema<StaticApi> {
+        CredentialSchema {
+            id: Buffer::from(b"ab12"),
+            type_: Buffer::from(b"Identity"),
+            attributes,
+            issuer: ManagedAddress::zero(),
+            issuer_did: Buffer::from(b"did:multiversx:01"),
+            version: Buffer::from(b"1.0"),
+            strict: true,
+            additional_properties: false,
+            created: 0,
+            deprecated_at: None,
+            superseded_by: None,
+        }
+    }
+
+    #[test]
+    fn strings_are_escaped() {
+        let mut writer = JsonWriter::<StaticApi>::new();
+        writer.string(b"a\"b\\c\n\x01");
+        assert_eq!(rendered(writer), b"\"a\\\"b\\\\c\\u000a\\u0001\"".to_vec());
+    }
+
+    #[test]
+    fn objects_and_arrays_are_comma_separated() {
+        let mut writer = JsonWriter::<StaticApi>::new();
+        writer.begin(b"{");
+        writer.key(b"a");
+        writer.begin(b"[");
+        writer.unsigned(1);
+        writer.raw(b"true");
+        writer.end(b"]");
+        writer.key(b"b");
+        writer.begin(b"{");
+        writer.end(b"}");
+        writer.end(b"}");
+        assert_eq!(rendered(writer), br#"{"a":[1,true],"b":{}}"#.to_vec());
+    }
+
+    #[test]
+    fn numbers_are_plain_json_literals() {
+        let mut writer = JsonWriter::<StaticApi>::new();
+        writer.begin(b"[");
+        writer.unsigned(0);
+        writer.unsigned(u128::MAX);
+        writer.decimal(-DECIMAL_SCALE / 2);
+        writer.decimal(DECIMAL_SCALE * 3 + 25 * DECIMAL_SCALE / 1000);
+        writer.decimal(1);
+        writer.value(DataType::Integer, b"-7");
+        writer.value(DataType::Decimal, b"1.50");
+        writer.value(DataType::Integer, b"seven");
+        writer.end(b"]");
+        assert_eq!(
+            rendered(writer),
+            br#"[0,340282366920938463463374607431768211455,-0.5,3.025,0.000000000000000001,-7,1.5,"seven"]"#.to_vec()
+        );
+    }
+
+    #[test]
+    fn schemas_render_as_vc_json_schemas() {
+        let mut name = constraints();
+        name.min_length = 1;
+        name.max_length = 64;
+        name.pattern = Buffer::from(b"^[A-Z].*$");
+        let mut age = constraints();
+        age.minimum = Buffer::from(b"18");
+        age.maximum = Buffer::from(b"150");
+        let mut birth_date = constraints();
+        birth_date.minimum = Buffer::from(b"1900-01-01");
+        let mut tags = constraints();
+        tags.item_type = Buffer::from(b"string");
+        tags.allowed_values.push(Buffer::from(b"a"));
+        tags.max_items = 2;
+
+        let mut attributes = ManagedVec::new();
+        attributes.push(attribute(b"name", b"string", true, name));
+        attributes.push(attribute(b"age", b"integer", false, age));
+        attributes.push(attribute(b"birthDate", b"date", true, birth_date));
+        attributes.push(attribute(b"tags", b"list", false, tags));
+
+        let json = render_json_schema(&schema(attributes));
+        let expected: &[u8] = concat!(
+            r#"{"$schema":"https://json-schema.org/draft/2020-12/schema","#,
+            r#""$id":"urn:multiversx:schema:ab12","title":"Identity","#,
+            r#""description":"Version 1.0 issued by did:multiversx:01","type":"object","#,
+            r#""properties":{"credentialSubject":{"type":"object","properties":{"#,
+            r#""name":{"type":"string","minLength":1,"maxLength":64,"pattern":"^[A-Z].*$"},"#,
+            r#""age":{"type":"integer","minimum":18,"maximum":150},"#,
+            r#""birthDate":{"type":"string","format":"date"},"#,
+            r#""tags":{"type":"array","items":{"type":"string","enum":["a"]},"maxItems":2}},"#,
+            r#""required":["name","birthDate"],"additionalProperties":false}},"#,
+            r#""required":["credentialSubject"]}"#
+        )
+        .as_bytes();
+        assert_eq!(json.to_boxed_bytes().as_slice(), expected);
+    }
+
+    #[test]
+    fn deprecated_open_schemas_are_flagged() {
+        let mut open = schema(ManagedVec::new());
+        open.additional_properties = true;
+        open.deprecated_at = Some(1);
+
+        let json = render_json_schema(&open).to_boxed_bytes();
+        let json = json.as_slice();
+        let contains = |needle: &[u8]| json.windows(needle.len()).any(|window| window == needle);
+        assert!(contains(br#""deprecated":true"#));
+        assert!(contains(br#""properties":{},"required":[]}"#));
+        assert!(!contains(b"additionalProperties"));
+    }
+}
@@ -0,0 +1,658 @@
+use crate::helpers::{days_from_civil, parse_digits, parse_iso8601_date, parse_iso8601_timestamp};
+
+// Claim values are UTF-8 text. Ordered types (integer, decimal, date, datetime)
+// map onto an i128 so that minimum/maximum bounds compare the same way for all
+// of them. List values are comma-separated items of a single item type.
+
+pub const LIST_SEPARATOR: u8 = b',';
+pub const MAX_DECIMALS: u32 = 18;
+pub const DECIMAL_SCALE: i128 = 1_000_000_000_000_000_000;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DataType {
+    String,
+    Integer,
+    Boolean,
+    Decimal,
+    Date,
+    DateTime,
+    Email,
+    Uri,
+    Enum,
+    List,
+}
+
+impl DataType {
+    pub fn parse(name: &[u8]) -> Option<Self> {
+        match name {
+            b"string" => Some(DataType::String),
+            b"integer" => Some(DataType::Integer),
+            b"boolean" => Some(DataType::Boolean),
+            b"decimal" => Some(DataType::Decimal),
+            b"date" => Some(DataType::Date),
+            b"datetime" => Some(DataType::DateTime),
+            b"email" => Some(DataType::Email),
+            b"uri" => Some(DataType::Uri),
+            b"enum" => Some(DataType::Enum),
+            b"list" => Some(DataType::List),
+            _ => None,
+        }
+    }
+
+    pub fn is_ordered(self) -> bool {
+        matches!(
+            self,
+            DataType::Integer | DataType::Decimal | DataType::Date | DataType::DateTime
+        )
+    }
+}
+
+// How an attribute is disclosed. Private and selectively disclosable claims are
+// stored on-chain as hashes only. Selectively disclosable claims are leaves of
+// the claims Merkle tree, so the holder can later prove them one at a time;
+// private claims are left out of the tree and can never be revealed through the
+// contract. Predicates carry a derived boolean (such as "over 18") instead of
+// the underlying value.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Disclosure {
+    Public,
+    Private,
+    SelectivelyDisclosable,
+    Predicate,
+}
+
+impl Disclosure {
+    pub fn parse(name: &[u8]) -> Option<Self> {
+        match name {
+            b"public" => Some(Disclosure::Public),
+            b"private" => Some(Disclosure::Private),
+            b"selectively-disclosable" => Some(Disclosure::SelectivelyDisclosable),
+            b"predicate" => Some(Disclosure::Predicate),
+            _ => None,
+        }
+    }
+
+    pub fn is_hash_only(self) -> bool {
+        matches!(self, Disclosure::Private | Disclosure::SelectivelyDisclosable)
+    }
+
+    pub fn is_revealable(self) -> bool {
+        self != Disclosure::Private
+    }
+}
+
+pub const CLAIM_HASH_LEN: usize = 32;
+
+fn split_sign(bytes: &[u8]) -> (bool, &[u8]) {
+    match bytes.first() {
+        Some(b'-') => (true, &bytes[1..]),
+        Some(b'+') => (false, &bytes[1..]),
+        _ => (false, bytes),
+    }
+}
+
+fn all_digits(bytes: &[u8]) -> bool {
+    bytes.iter().all(u8::is_ascii_digit)
+}
+
+pub fn parse_integer(bytes: &[u8]) -> Option<i64> {
+    let (negative, digits) = split_sign(bytes);
+    if digits.is_empty() || !all_digits(digits) {
+        return None;
+    }
+
+    // Negative values accumulate downwards so that i64::MIN is representable
+    let mut value = 0i64;
+    for &digit in digits {
+        let digit = (digit - b'0') as i64;
+        value = value.checked_mul(10)?;
+        value = if negative {
+            value.checked_sub(digit)?
+        } else {
+            value.checked_add(digit)?
+        };
+    }
+    Some(value)
+}
+
+// Returns the value scaled by 10^18 together with its number of decimal places
+pub fn parse_decimal(bytes: &[u8]) -> Option<(i128, u32)> {
+    let (negative, unsigned) = split_sign(bytes);
+    let (whole, fraction) = match unsigned.iter().position(|&b| b == b'.') {
+        Some(dot) if dot + 1 < unsigned.len() => (&unsigned[..dot], &unsigned[dot + 1..]),
+        Some(_) => return None,
+        None => (unsigned, &unsigned[unsigned.len()..]),
+    };
+    if whole.is_empty()
+        || !all_digits(whole)
+        || !all_digits(fraction)
+        || fraction.len() > MAX_DECIMALS as usize
+    {
+        return None;
+    }
+
+    let mut value = 0i128;
+    for &digit in whole {
+        value = value.checked_mul(10)?.checked_add((digit - b'0') as i128)?;
+    }
+    value = value.checked_mul(DECIMAL_SCALE)?;
+    let mut unit = DECIMAL_SCALE;
+    for &digit in fraction {
+        unit /= 10;
+        value = value.checked_add(((digit - b'0') as i128).checked_mul(unit)?)?;
+    }
+    Some((if negative { -value } else { value }, fraction.len() as u32))
+}
+
+pub fn ordered_value(data_type: DataType, bytes: &[u8]) -> Option<i128> {
+    match data_type {
+        DataType::Integer => parse_integer(bytes).map(i128::from),
+        DataType::Decimal => parse_decimal(bytes).map(|(value, _)| value),
+        DataType::Date => {
+            parse_iso8601_date(bytes).map(|(year, month, day)| days_from_civil(year, month, day) as i128)
+        }
+        DataType::DateTime => parse_iso8601_timestamp(bytes).map(i128::from),
+        _ => None,
+    }
+}
+
+pub fn is_email(bytes: &[u8]) -> bool {
+    let at = match bytes.iter().position(|&b| b == b'@') {
+        Some(at) => at,
+        None => return false,
+    };
+    let (local, domain) = (&bytes[..at], &bytes[at + 1..]);
+    if local.is_empty()
+        || local.len() > 64
+        || local[0] == b'.'
+        || local[local.len() - 1] == b'.'
+        || !local
+            .iter()
+            .all(|&b| b.is_ascii_alphanumeric() || b"._%+-".contains(&b))
+    {
+        return false;
+    }
+
+    let mut labels = 0;
+    for label in domain.split(|&b| b == b'.') {
+        if label.is_empty()
+            || label.len() > 63
+            || label[0] == b'-'
+            || label[label.len() - 1] == b'-'
+            || !label.iter().all(|&b| b.is_ascii_alphanumeric() || b == b'-')
+        {
+            return false;
+        }
+        labels += 1;
+    }
+    labels >= 2
+}
+
+// Absolute URI (RFC 3986): a scheme, a colon and a remainder without whitespace
+pub fn is_uri(bytes: &[u8]) -> bool {
+    let colon = match bytes.iter().position(|&b| b == b':') {
+        Some(colon) => colon,
+        None => return false,
+    };
+    let (scheme, rest) = (&bytes[..colon], &bytes[colon + 1..]);
+    !scheme.is_empty()
+        && scheme[0].is_ascii_alphabetic()
+        && scheme
+            .iter()
+            .all(|&b| b.is_ascii_alphanumeric() || b"+-.".contains(&b))
+        && !rest.is_empty()
+        && rest.iter().all(u8::is_ascii_graphic)
+}
+
+// Whether a scalar value is well formed for its data type
+pub fn conforms_to(data_type: DataType, bytes: &[u8]) -> bool {
+    match data_type {
+        DataType::String | DataType::Enum => core::str::from_utf8(bytes).is_ok(),
+        DataType::Integer => parse_integer(bytes).is_some(),
+        DataType::Boolean => matches!(bytes, b"true" | b"false"),
+        DataType::Decimal => parse_decimal(bytes).is_some(),
+        DataType::Date => parse_iso8601_date(bytes).is_some(),
+        DataType::DateTime => parse_iso8601_timestamp(bytes).is_some(),
+        DataType::Email => is_email(bytes),
+        DataType::Uri => is_uri(bytes),
+        DataType::List => false,
+    }
+}
+
+// Length in characters of a UTF-8 value
+pub fn char_count(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&b| b & 0xc0 != 0x80).count()
+}
+
+pub fn list_items(bytes: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let items = if bytes.is_empty() { None } else { Some(bytes) };
+    items
+        .into_iter()
+        .flat_map(|bytes| bytes.split(|&b| b == LIST_SEPARATOR))
+}
+
+// Regex-like patterns: literals, `.`, `\d` `\w` `\s`, character classes such as
+// `[A-Z0-9]` or `[^-]`, and the `* + ? {n} {n,} {n,m}` quantifiers. Patterns
+// always match the whole value; leading `^` and trailing `$` are accepted.
+
+enum Atom<'a> {
+    Any,
+    Byte(u8),
+    Digit,
+    Word,
+    Space,
+    Class { set: &'a [u8], negated: bool },
+}
+
+impl Atom<'_> {
+    fn matches(&self, byte: u8) -> bool {
+        match self {
+            Atom::Any => true,
+            Atom::Byte(expected) => *expected == byte,
+            Atom::Digit => byte.is_ascii_digit(),
+            Atom::Word => byte.is_ascii_alphanumeric() || byte == b'_',
+            Atom::Space => byte.is_ascii_whitespace(),
+            Atom::Class { set, negated } => class_contains(set, byte) != *negated,
+        }
+    }
+}
+
+fn escaped_atom(byte: u8) -> Atom<'static> {
+    match byte {
+        b'd' => Atom::Digit,
+        b'w' => Atom::Word,
+        b's' => Atom::Space,
+        other => Atom::Byte(other),
+    }
+}
+
+fn class_contains(set: &[u8], byte: u8) -> bool {
+    let mut index = 0;
+    while index < set.len() {
+        let (atom, len) = if set[index] == b'\\' && index + 1 < set.len() {
+            (escaped_atom(set[index + 1]), 2)
+        } else {
+            (Atom::Byte(set[index]), 1)
+        };
+
+        if let Atom::Byte(low) = atom {
+            if set.get(index + len) == Some(&b'-') && index + len + 1 < set.len() {
+                if (low..=set[index + len + 1]).contains(&byte) {
+                    return true;
+                }
+                index += len + 2;
+                continue;
+            }
+        }
+        if atom.matches(byte) {
+            return true;
+        }
+        index += len;
+    }
+    false
+}
+
+// Parses one atom and returns it with the number of pattern bytes it spans
+fn parse_atom(pattern: &[u8]) -> Option<(Atom<'_>, usize)> {
+    match pattern[0] {
+        b'.' => Some((Atom::Any, 1)),
+        b'\\' => pattern.get(1).map(|&b| (escaped_atom(b), 2)),
+        b'[' => {
+            let negated = pattern.get(1) == Some(&b'^');
+            let start = if negated { 2 } else { 1 };
+            // A `]` right after the opening bracket is taken literally
+            let mut end = start;
+            loop {
+                match pattern.get(end)? {
+                    b'\\' => end += 2,
+                    b']' if end > start => break,
+                    _ => end += 1,
+                }
+            }
+            Some((
+                Atom::Class {
+                    set: &pattern[start..end],
+                    negated,
+                },
+                end + 1,
+            ))
+        }
+        // Groups and alternation are not supported, quantifiers need an atom
+        b'*' | b'+' | b'?' | b'{' | b'(' | b')' | b'|' => None,
+        byte => Some((Atom::Byte(byte), 1)),
+    }
+}
+
+// Returns (min, max, pattern bytes spanned); no quantifier means exactly once
+fn parse_quantifier(pattern: &[u8]) -> Option<(usize, usize, usize)> {
+    match pattern.first() {
+        Some(b'*') => Some((0, usize::MAX, 1)),
+        Some(b'+') => Some((1, usize::MAX, 1)),
+        Some(b'?') => Some((0, 1, 1)),
+        Some(b'{') => {
+            let close = pattern.iter().position(|&b| b == b'}')?;
+            let body = &pattern[1..close];
+            let (min, max) = match body.iter().position(|&b| b == b',') {
+                Some(comma) if comma + 1 == body.len() => {
+                    (parse_digits(&body[..comma])? as usize, usize::MAX)
+                }
+                Some(comma) => (
+                    parse_digits(&body[..comma])? as usize,
+                    parse_digits(&body[comma + 1..])? as usize,
+                ),
+                None => {
+                    let count = parse_digits(body)? as usize;
+                    (count, count)
+                }
+            };
+            if min > max {
+                return None;
+            }
+            Some((min, max, close + 1))
+        }
+        _ => Some((1, 1, 0)),
+    }
+}
+
+pub fn strip_anchors(pattern: &[u8]) -> &[u8] {
+    let pattern = pattern.strip_prefix(b"^").unwrap_or(pattern);
+    match pattern.strip_suffix(b"$") {
+        Some(stripped) if !stripped.ends_with(b"\\") => stripped,
+        _ => pattern,
+    }
+}
+
+// Patterns are matched by simulating their NFA, so matching stays linear in
+// the value length. A state is a quantified atom together with the number of
+// repetitions matched so far; unbounded repetitions beyond the minimum share a
+// state. Patterns needing more than `MAX_PATTERN_STATES` states are rejected.
+const MAX_PATTERN_STATES: usize = 256;
+
+type StateSet = [u64; MAX_PATTERN_STATES / 64];
+
+fn contains_state(states: &StateSet, state: usize) -> bool {
+    states[state / 64] & (1 << (state % 64)) != 0
+}
+
+fn insert_state(states: &mut StateSet, state: usize) {
+    states[state / 64] |= 1 << (state % 64);
+}
+
+// Highest repetition count tracked for a quantifier
+fn repetition_cap(min: usize, max: usize) -> usize {
+    if max == usize::MAX {
+        min
+    } else {
+        max
+    }
+}
+
+// Iterates over the quantified atoms of a pattern, stopping at the first
+// invalid one
+struct PatternItems<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> Iterator for PatternItems<'a> {
+    type Item = Option<(Atom<'a>, usize, usize)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        let item = parse_atom(self.rest).and_then(|(atom, atom_len)| {
+            let (min, max, quantifier_len) = parse_quantifier(&self.rest[atom_len..])?;
+            Some((atom, min, max, atom_len + quantifier_len))
+        });
+        match item {
+            Some((atom, min, max, len)) => {
+                self.rest = &self.rest[len..];
+                Some(Some((atom, min, max)))
+            }
+            None => {
+                self.rest = &[];
+                Some(None)
+            }
+        }
+    }
+}
+
+fn pattern_items(pattern: &[u8]) -> PatternItems<'_> {
+    PatternItems {
+        rest: strip_anchors(pattern),
+    }
+}
+
+// Number of NFA states including the final one, or None for an invalid or
+// too complex pattern
+fn pattern_state_count(pattern: &[u8]) -> Option<usize> {
+    let mut count = 1usize;
+    for item in pattern_items(pattern) {
+        let (_, min, max) = item?;
+        count = count.checked_add(repetition_cap(min, max).checked_add(1)?)?;
+        if count > MAX_PATTERN_STATES {
+            return None;
+        }
+    }
+    Some(count)
+}
+
+// Follows the empty transitions out of every state whose minimum is reached
+fn close_states(pattern: &[u8], states: &mut StateSet) {
+    let mut offset = 0;
+    for (_, min, max) in pattern_items(pattern).flatten() {
+        let cap = repetition_cap(min, max);
+        if (min..=cap).any(|count| contains_state(states, offset + count)) {
+            insert_state(states, offset + cap + 1);
+        }
+        offset += cap + 1;
+    }
+}
+
+fn step_states(pattern: &[u8], states: &StateSet, byte: u8) -> StateSet {
+    let mut next = [0u64; MAX_PATTERN_STATES / 64];
+    let mut offset = 0;
+    for (atom, min, max) in pattern_items(pattern).flatten() {
+        let cap = repetition_cap(min, max);
+        if atom.matches(byte) {
+            for count in 0..=cap {
+                if !contains_state(states, offset + count) {
+                    continue;
+                }
+                if count < cap {
+                    insert_state(&mut next, offset + count + 1);
+                } else if max == usize::MAX {
+                    insert_state(&mut next, offset + count);
+                }
+            }
+        }
+        offset += cap + 1;
+    }
+    close_states(pattern, &mut next);
+    next
+}
+
+pub fn is_valid_pattern(pattern: &[u8]) -> bool {
+    pattern_state_count(pattern).is_some()
+}
+
+pub fn matches_pattern(pattern: &[u8], value: &[u8]) -> bool {
+    let state_count = match pattern_state_count(pattern) {
+        Some(count) => count,
+        None => return false,
+    };
+
+    let mut states = [0u64; MAX_PATTERN_STATES / 64];
+    insert_state(&mut states, 0);
+    close_states(pattern, &mut states);
+    for &byte in value {
+        states = step_states(pattern, &states, byte);
+        if states.iter().all(|&word| word == 0) {
+            return false;
+        }
+    }
+    contains_state(&states, state_count - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integers_parse_to_the_i64_bounds() {
+        assert_eq!(parse_integer(b"0"), Some(0));
+        assert_eq!(parse_integer(b"+42"), Some(42));
+        assert_eq!(parse_integer(b"-42"), Some(-42));
+        assert_eq!(parse_integer(b"9223372036854775807"), Some(i64::MAX));
+        assert_eq!(parse_integer(b"-9223372036854775808"), Some(i64::MIN));
+        assert_eq!(parse_integer(b"9223372036854775808"), None);
+        assert_eq!(parse_integer(b"-9223372036854775809"), None);
+        assert_eq!(parse_integer(b""), None);
+        assert_eq!(parse_integer(b"-"), None);
+        assert_eq!(parse_integer(b"1.0"), None);
+        assert_eq!(parse_integer(b" 1"), None);
+    }
+
+    #[test]
+    fn decimals_are_scaled_and_bounded() {
+        assert_eq!(parse_decimal(b"1"), Some((DECIMAL_SCALE, 0)));
+        assert_eq!(parse_decimal(b"-1.5"), Some((-DECIMAL_SCALE * 3 / 2, 1)));
+        assert_eq!(parse_decimal(b"0.000000000000000001"), Some((1, 18)));
+        assert_eq!(parse_decimal(b"0.0000000000000000001"), None);
+        assert_eq!(parse_decimal(b"1."), None);
+        assert_eq!(parse_decimal(b".5"), None);
+        assert_eq!(parse_decimal(b"1.2.3"), None);
+        assert_eq!(
+            parse_decimal(b"170141183460469231731"),
+            Some((170_141_183_460_469_231_731 * DECIMAL_SCALE, 0))
+        );
+        assert_eq!(parse_decimal(b"170141183460469231731.999999999999999999"), None);
+        assert_eq!(parse_decimal(b"170141183460469231732"), None);
+    }
+
+    #[test]
+    fn dates_follow_the_gregorian_calendar() {
+        assert_eq!(parse_iso8601_date(b"2024-02-29"), Some((2024, 2, 29)));
+        assert_eq!(parse_iso8601_date(b"2000-02-29"), Some((2000, 2, 29)));
+        assert_eq!(parse_iso8601_date(b"1900-02-29"), None);
+        assert_eq!(parse_iso8601_date(b"2023-02-29"), None);
+        assert_eq!(parse_iso8601_date(b"2023-04-31"), None);
+        assert_eq!(parse_iso8601_date(b"2023-12-31"), Some((2023, 12, 31)));
+        assert_eq!(parse_iso8601_date(b"2023-13-01"), None);
+        assert_eq!(parse_iso8601_date(b"2023-00-10"), None);
+        assert_eq!(parse_iso8601_date(b"2023-1-10"), None);
+        assert_eq!(parse_iso8601_date(b"2023/01/10"), None);
+
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        assert_eq!(
+            days_from_civil(2024, 3, 1) - days_from_civil(2024, 2, 28),
+            2
+        );
+    }
+
+    #[test]
+    fn date_times_convert_to_unix_timestamps() {
+        assert_eq!(parse_iso8601_timestamp(b"1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(parse_iso8601_timestamp(b"2024-02-29T12:30:45Z"), Some(1_709_209_845));
+        assert_eq!(parse_iso8601_timestamp(b"2023-12-31T23:59:59Z"), Some(1_704_067_199));
+        assert_eq!(parse_iso8601_timestamp(b"2023-02-29T00:00:00Z"), None);
+        assert_eq!(parse_iso8601_timestamp(b"2023-01-01T24:00:00Z"), None);
+        assert_eq!(parse_iso8601_timestamp(b"2023-01-01T00:60:00Z"), None);
+        assert_eq!(parse_iso8601_timestamp(b"2023-01-01T00:00:00"), None);
+        assert_eq!(parse_iso8601_timestamp(b"2023-01-01 00:00:00Z"), None);
+        assert_eq!(parse_iso8601_timestamp(b"1969-12-31T23:59:59Z"), None);
+    }
+
+    #[test]
+    fn dates_order_by_day() {
+        assert!(
+            ordered_value(DataType::Date, b"2024-02-28") < ordered_value(DataType::Date, b"2024-02-29")
+        );
+        assert_eq!(ordered_value(DataType::Date, b"2023-02-29"), None);
+        assert_eq!(ordered_value(DataType::Integer, b"-9223372036854775808"), Some(i64::MIN as i128));
+    }
+
+    #[test]
+    fn emails_need_a_local_part_and_a_dotted_domain() {
+        assert!(is_email(b"alice@example.com"));
+        assert!(is_email(b"first.last+tag@sub.example.org"));
+        assert!(!is_email(b"alice@localhost"));
+        assert!(!is_email(b"@example.com"));
+        assert!(!is_email(b".alice@example.com"));
+        assert!(!is_email(b"alice@-example.com"));
+        assert!(!is_email(b"alice@example..com"));
+        assert!(!is_email(b"alice@@example.com"));
+        assert!(!is_email(b"alice example@example.com"));
+    }
+
+    #[test]
+    fn uris_need_a_scheme_and_a_remainder() {
+        assert!(is_uri(b"https://example.com/path?query=1"));
+        assert!(is_uri(b"did:multiversx:abc"));
+        assert!(is_uri(b"urn:isbn:0451450523"));
+        assert!(!is_uri(b"example.com"));
+        assert!(!is_uri(b"1http://example.com"));
+        assert!(!is_uri(b"https:"));
+        assert!(!is_uri(b"https://exa mple.com"));
+    }
+
+    #[test]
+    fn patterns_match_the_whole_value() {
+        assert!(matches_pattern(b"", b""));
+        assert!(!matches_pattern(b"", b"a"));
+        assert!(matches_pattern(b"^abc$", b"abc"));
+        assert!(matches_pattern(b"abc", b"abc"));
+        assert!(!matches_pattern(b"abc", b"abcd"));
+        assert!(!matches_pattern(b"^abc$", b"xabc"));
+        assert!(matches_pattern(b"^$", b""));
+        assert!(matches_pattern(b"a\\$", b"a$"));
+        assert!(!matches_pattern(b"a\\$", b"a"));
+    }
+
+    #[test]
+    fn patterns_support_classes_and_quantifiers() {
+        assert!(matches_pattern(b"\\d{3}-\\d{4}", b"555-1234"));
+        assert!(!matches_pattern(b"\\d{3}-\\d{4}", b"555-123"));
+        assert!(matches_pattern(b"[A-Z][a-z]*", b"Hello"));
+        assert!(!matches_pattern(b"[A-Z][a-z]*", b"hello"));
+        assert!(matches_pattern(b"[^-]+", b"abc"));
+        assert!(!matches_pattern(b"[^-]+", b"a-c"));
+        assert!(matches_pattern(b"\\w+\\s\\w+", b"hello world"));
+        assert!(matches_pattern(b"colou?r", b"color"));
+        assert!(matches_pattern(b"colou?r", b"colour"));
+        assert!(matches_pattern(b"a{2,}", b"aaaaa"));
+        assert!(!matches_pattern(b"a{2,3}", b"aaaa"));
+        assert!(matches_pattern(b".*a.*b", b"xxaxxb"));
+    }
+
+    #[test]
+    fn escaped_metacharacters_are_literals() {
+        assert!(matches_pattern(b"a\\.b", b"a.b"));
+        assert!(!matches_pattern(b"a\\.b", b"axb"));
+        assert!(matches_pattern(b"\\*\\+\\?", b"*+?"));
+        assert!(matches_pattern(b"[\\]]", b"]"));
+        assert!(matches_pattern(b"[]a]+", b"]a]"));
+        assert!(matches_pattern(b"\\$", b"$"));
+    }
+
+    #[test]
+    fn pathological_patterns_match_in_linear_time() {
+        let value = [b'a'; 40];
+        assert!(!matches_pattern(b"a*a*a*a*a*a*a*a*b", &value));
+        assert!(matches_pattern(b"a*a*a*a*a*a*a*a*", &value));
+    }
+
+    #[test]
+    fn invalid_or_oversized_patterns_are_rejected() {
+        assert!(is_valid_pattern(b""));
+        assert!(is_valid_pattern(b"^[a-z]{1,32}$"));
+        assert!(!is_valid_pattern(b"*a"));
+        assert!(!is_valid_pattern(b"a{3,2}"));
+        assert!(!is_valid_pattern(b"(a|b)"));
+        assert!(!is_valid_pattern(b"[abc"));
+        assert!(!is_valid_pattern(b"a{1,1000}"));
+        assert!(!is_valid_pattern(b"a{99999999999}"));
+    }
+}
@@ -0,0 +1,328 @@
+use multiversx_sc::imports::*;
+
+// Bitstring Status List encoding: `u` (multibase base64url, no padding) of the
+// GZIP-compressed bitstring. Compression uses a single fixed-Huffman deflate
+// block with run-length matches, which keeps sparse status lists very small.
+
+const GZIP_HEADER: [u8; 10] = [0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff];
+const BASE64URL_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+const READ_CHUNK_LEN: usize = 256;
+
+const MIN_MATCH_LEN: usize = 3;
+const MAX_MATCH_LEN: usize = 258;
+const END_OF_BLOCK: u32 = 256;
+
+// Base lengths and extra bits of the length symbols 257..=285
+const LENGTH_BASES: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115,
+    131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA_BITS: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+pub fn crc32_update(mut crc: u32, bytes: &[u8]) -> u32 {
+    crc = !crc;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+struct DeflateWriter<M: ManagedTypeApi> {
+    output: ManagedBuffer<M>,
+    bit_buffer: u32,
+    bit_count: u32,
+}
+
+impl<M: ManagedTypeApi> DeflateWriter<M> {
+    fn new(output: ManagedBuffer<M>) -> Self {
+        DeflateWriter {
+            output,
+            bit_buffer: 0,
+            bit_count: 0,
+        }
+    }
+
+    fn write_bits(&mut self, value: u32, count: u32) {
+        self.bit_buffer |= value << self.bit_count;
+        self.bit_count += count;
+        while self.bit_count >= 8 {
+            self.output.append_bytes(&[(self.bit_buffer & 0xff) as u8]);
+            self.bit_buffer >>= 8;
+            self.bit_count -= 8;
+        }
+    }
+
+    // Huffman codes are packed starting from their most significant bit
+    fn write_code(&mut self, code: u32, length: u32) {
+        let mut reversed = 0u32;
+        for bit in 0..length {
+            reversed |= ((code >> bit) & 1) << (length - 1 - bit);
+        }
+        self.write_bits(reversed, length);
+    }
+
+    fn write_symbol(&mut self, symbol: u32) {
+        match symbol {
+            0..=143 => self.write_code(0x30 + symbol, 8),
+            144..=255 => self.write_code(0x190 + symbol - 144, 9),
+            256..=279 => self.write_code(symbol - 256, 7),
+            _ => self.write_code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    // Repeats the previous byte `length` times (a match at distance 1)
+    fn write_repeat(&mut self, length: usize) {
+        let index = LENGTH_BASES
+            .iter()
+            .rposition(|&base| base as usize <= length)
+            .unwrap_or(0);
+        self.write_symbol(257 + index as u32);
+        self.write_bits(
+            (length - LENGTH_BASES[index] as usize) as u32,
+            LENGTH_EXTRA_BITS[index] as u32,
+        );
+        // Distance code 0 (distance 1) has a 5 bit code and no extra bits
+        self.write_code(0, 5);
+    }
+
+    fn write_run(&mut self, byte: u8, mut length: usize) {
+        self.write_symbol(byte as u32);
+        length -= 1;
+        while length >= MIN_MATCH_LEN {
+            let match_len = length.min(MAX_MATCH_LEN);
+            self.write_repeat(match_len);
+            length -= match_len;
+        }
+        for _ in 0..length {
+            self.write_symbol(byte as u32);
+        }
+    }
+
+    fn finish(mut self) -> ManagedBuffer<M> {
+        self.write_symbol(END_OF_BLOCK);
+        if self.bit_count > 0 {
+            self.write_bits(0, 8 - self.bit_count);
+        }
+        self.output
+    }
+}
+
+pub fn gzip_compress<M: ManagedTypeApi>(data: &ManagedBuffer<M>) -> ManagedBuffer<M> {
+    let mut writer = DeflateWriter::new(ManagedBuffer::new_from_bytes(&GZIP_HEADER));
+    // Final block (1) with fixed Huffman codes (01)
+    writer.write_bits(1, 1);
+    writer.write_bits(1, 2);
+
+    let total_len = data.len();
+    let mut crc = 0u32;
+    let mut run: Option<(u8, usize)> = None;
+    let mut chunk = [0u8; READ_CHUNK_LEN];
+    let mut position = 0;
+    while position < total_len {
+        let chunk_len = READ_CHUNK_LEN.min(total_len - position);
+        let _ = data.load_slice(position, &mut chunk[..chunk_len]);
+        crc = crc32_update(crc, &chunk[..chunk_len]);
+
+        for &byte in &chunk[..chunk_len] {
+            run = match run {
+                Some((current, length)) if current == byte => Some((current, length + 1)),
+                Some((current, length)) => {
+                    writer.write_run(current, length);
+                    Some((byte, 1))
+                }
+                None => Some((byte, 1)),
+            };
+        }
+        position += chunk_len;
+    }
+    if let Some((current, length)) = run {
+        writer.write_run(current, length);
+    }
+
+    let mut output = writer.finish();
+    output.append_bytes(&crc.to_le_bytes());
+    output.append_bytes(&(total_len as u32).to_le_bytes());
+    output
+}
+
+pub fn multibase_base64url<M: ManagedTypeApi>(data: &ManagedBuffer<M>) -> ManagedBuffer<M> {
+    let bytes = data.to_boxed_bytes();
+    let mut encoded = ManagedBuffer::new_from_bytes(b"u");
+    for group in bytes.as_slice().chunks(3) {
+        let mut block = [0u8; 3];
+        block[..group.len()].copy_from_slice(group);
+        let value = ((block[0] as u32) << 16) | ((block[1] as u32) << 8) | block[2] as u32;
+
+        let mut digits = [0u8; 4];
+        for (index, digit) in digits.iter_mut().enumerate() {
+            *digit = BASE64URL_ALPHABET[((value >> (18 - 6 * index)) & 0x3f) as usize];
+        }
+        encoded.append_bytes(&digits[..group.len() + 1]);
+    }
+    encoded
+}
+
+// Bit 0 is the most significant bit of the first byte
+pub fn write_status_bit<M: ManagedTypeApi>(bits: &mut ManagedBuffer<M>, index: u64, value: bool) {
+    let position = (index / 8) as usize;
+    let mask = 0x80u8 >> (index % 8);
+
+    let mut byte = [0u8; 1];
+    let _ = bits.load_slice(position, &mut byte);
+    if value {
+        byte[0] |= mask;
+    } else {
+        byte[0] &= !mask;
+    }
+    let _ = bits.set_slice(position, &byte);
+}
+
+pub fn read_status_bit<M: ManagedTypeApi>(bits: &ManagedBuffer<M>, index: u64) -> bool {
+    let mut byte = [0u8; 1];
+    let _ = bits.load_slice((index / 8) as usize, &mut byte);
+    byte[0] & (0x80u8 >> (index % 8)) != 0
+}
+
+// Bitstrings changed while processing a batch, stored once when it completes
+pub struct PendingStatusBits<M: ManagedTypeApi> {
+    pub list_ids: ManagedVec<M, u64>,
+    pub bits: ManagedVec<M, ManagedBuffer<M>>,
+}
+
+impl<M: ManagedTypeApi> PendingStatusBits<M> {
+    pub fn new() -> Self {
+        PendingStatusBits {
+            list_ids: ManagedVec::new(),
+            bits: ManagedVec::new(),
+        }
+    }
+
+    pub fn position(&self, list_id: u64) -> Option<usize> {
+        self.list_ids.iter().position(|id| id == list_id)
+    }
+
+    pub fn push(&mut self, list_id: u64, bits: ManagedBuffer<M>) -> usize {
+        self.list_ids.push(list_id);
+        self.bits.push(bits);
+        self.list_ids.len() - 1
+    }
+
+    pub fn write(&mut self, position: usize, index: u64, value: bool) {
+        write_status_bit(&mut self.bits.get_mut(position), index, value);
+    }
+}
+
+pub fn zeroed_bitstring<M: ManagedTypeApi>(size_in_bits: u64) -> ManagedBuffer<M> {
+    let zeros = [0u8; READ_CHUNK_LEN];
+    let mut remaining = (size_in_bits / 8) as usize;
+    let mut bits = ManagedBuffer::new();
+    while remaining > 0 {
+        let len = remaining.min(READ_CHUNK_LEN);
+        bits.append_bytes(&zeros[..len]);
+        remaining -= len;
+    }
+    bits
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use multiversx_sc_scenario::api::StaticApi;
+    use std::{vec, vec::Vec};
+
+    fn buffer(bytes: &[u8]) -> ManagedBuffer<StaticApi> {
+        ManagedBuffer::new_from_bytes(bytes)
+    }
+
+    fn gunzip(compressed: &ManagedBuffer<StaticApi>) -> Vec<u8> {
+        let bytes = compressed.to_boxed_bytes();
+        let bytes = bytes.as_slice();
+        assert_eq!(&bytes[..10], &GZIP_HEADER);
+
+        let (deflated, trailer) = bytes[10..].split_at(bytes.len() - 18);
+        let inflated = miniz_oxide::inflate::decompress_to_vec(deflated).unwrap();
+        assert_eq!(&trailer[..4], &crc32_update(0, &inflated).to_le_bytes());
+        assert_eq!(&trailer[4..], &(inflated.len() as u32).to_le_bytes());
+        inflated
+    }
+
+    #[test]
+    fn crc32_matches_known_vectors() {
+        assert_eq!(crc32_update(0, b""), 0);
+        assert_eq!(crc32_update(0, b"a"), 0xE8B7_BE43);
+        assert_eq!(crc32_update(0, b"123456789"), 0xCBF4_3926);
+        assert_eq!(
+            crc32_update(0, b"The quick brown fox jumps over the lazy dog"),
+            0x414F_A339
+        );
+        assert_eq!(crc32_update(crc32_update(0, b"12345"), b"6789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn base64url_encodes_every_tail_length() {
+        assert_eq!(multibase_base64url(&buffer(b"")), buffer(b"u"));
+        assert_eq!(multibase_base64url(&buffer(b"M")), buffer(b"uTQ"));
+        assert_eq!(multibase_base64url(&buffer(b"Ma")), buffer(b"uTWE"));
+        assert_eq!(multibase_base64url(&buffer(b"Man")), buffer(b"uTWFu"));
+        assert_eq!(multibase_base64url(&buffer(&[0xfb])), buffer(b"u-w"));
+        assert_eq!(multibase_base64url(&buffer(&[0xfb, 0xff])), buffer(b"u-_8"));
+        assert_eq!(multibase_base64url(&buffer(&[0xfb, 0xff, 0xbf])), buffer(b"u-_-_"));
+        assert_eq!(
+            multibase_base64url(&buffer(&[0xfb, 0xff, 0xbf, 0xfe])),
+            buffer(b"u-_-__g")
+        );
+    }
+
+    #[test]
+    fn gzip_runs_round_trip() {
+        for length in [1usize, 2, 3, 4, 257, 258, 259, 260, 516, 1000] {
+            let data = vec![0xa5u8; length];
+            assert_eq!(gunzip(&gzip_compress(&buffer(&data))), data, "run of {length}");
+        }
+    }
+
+    #[test]
+    fn gzip_mixed_data_round_trips() {
+        let mut data = Vec::new();
+        for (byte, length) in [(0u8, 3usize), (1, 1), (0, 258), (0xff, 2), (0, 259), (7, 1)] {
+            data.extend(core::iter::repeat_n(byte, length));
+        }
+        data.extend(0u8..=255);
+        assert_eq!(gunzip(&gzip_compress(&buffer(&data))), data);
+        assert_eq!(gunzip(&gzip_compress(&buffer(b""))), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn gzip_status_list_round_trips() {
+        let mut bits = zeroed_bitstring::<StaticApi>(131_072);
+        for index in [0u64, 7, 8, 1_000, 131_071] {
+            write_status_bit(&mut bits, index, true);
+        }
+
+        let inflated = gunzip(&gzip_compress(&bits));
+        assert_eq!(inflated.len(), 16_384);
+        assert_eq!(inflated[0], 0x81);
+        assert_eq!(inflated[1], 0x80);
+        assert_eq!(inflated[125], 0x80);
+        assert_eq!(inflated[16_383], 0x01);
+        assert_eq!(inflated.iter().filter(|&&byte| byte != 0).count(), 4);
+    }
+
+    #[test]
+    fn status_bits_read_back() {
+        let mut bits = zeroed_bitstring::<StaticApi>(16);
+        write_status_bit(&mut bits, 9, true);
+        assert!(read_status_bit(&bits, 9));
+        assert!(!read_status_bit(&bits, 8));
+        write_status_bit(&mut bits, 9, false);
+        assert_eq!(bits, buffer(&[0, 0]));
+    }
+}
@@ -12,15 +12,63 @@ pub trait StorageModule {
     #[storage_mapper("didDocuments")]
     fn did_documents(&self, did: &ManagedBuffer) -> SingleValueMapper<DidDocument<Self::Api>>;
 
+    #[storage_mapper("didDocumentVersions")]
+    fn did_document_versions(&self, did: &ManagedBuffer) -> VecMapper<DidDocumentVersion<Self::Api>>;
+
+    #[view(getDidProposal)]
+    #[storage_mapper("didProposals")]
+    fn did_proposals(&self, id: &u64) -> SingleValueMapper<DidProposal<Self::Api>>;
+
+    #[storage_mapper("nextDidProposalId")]
+    fn next_did_proposal_id(&self) -> SingleValueMapper<u64>;
+
+    #[view(getRecoveryConfig)]
+    #[storage_mapper("recoveryConfig")]
+    fn recovery_config(&self, did: &ManagedBuffer) -> SingleValueMapper<RecoveryConfig<Self::Api>>;
+
+    #[view(getRecoveryRequest)]
+    #[storage_mapper("recoveryRequest")]
+    fn recovery_request(&self, did: &ManagedBuffer) -> SingleValueMapper<RecoveryRequest<Self::Api>>;
+
     #[storage_mapper("addressToDid")]
     fn address_to_did(&self, address: &ManagedAddress) -> SingleValueMapper<ManagedBuffer>;
 
+    #[storage_mapper("didByController")]
+    fn did_by_controller(&self, controller: &ManagedAddress) -> UnorderedSetMapper<ManagedBuffer>;
+
+    #[storage_mapper("didNonce")]
+    fn did_nonce(&self, address: &ManagedAddress) -> SingleValueMapper<u64>;
+
+    #[view(getPendingDidTransfer)]
+    #[storage_mapper("pendingDidTransfer")]
+    fn pending_did_transfer(&self, did: &ManagedBuffer) -> SingleValueMapper<ManagedAddress>;
+
     #[storage_mapper("credentialSchemas")]
     fn credential_schemas(&self, schema_id: &ManagedBuffer) -> SingleValueMapper<CredentialSchema<Self::Api>>;
 
+    #[storage_mapper("schemaIds")]
+    fn schema_ids(&self) -> VecMapper<ManagedBuffer>;
+
+    #[storage_mapper("issuerSchemas")]
+    fn issuer_schemas(&self, issuer: &ManagedAddress) -> VecMapper<ManagedBuffer>;
+
+    #[storage_mapper("schemasByType")]
+    fn schemas_by_type(&self, type_: &ManagedBuffer) -> VecMapper<ManagedBuffer>;
+
+    #[storage_mapper("schemaVersions")]
+    fn schema_versions(&self, issuer_did: &ManagedBuffer, type_: &ManagedBuffer) -> VecMapper<ManagedBuffer>;
+
+    #[view(getSchemaDeprecationGracePeriod)]
+    #[storage_mapper("schemaDeprecationGracePeriod")]
+    fn schema_deprecation_grace_period(&self) -> SingleValueMapper<u64>;
+
     #[storage_mapper("credentials")]
     fn credentials(&self, id: &u64) -> SingleValueMapper<Credential<Self::Api>>;
 
+    #[view(getCredentialRevocation)]
+    #[storage_mapper("credentialRevocations")]
+    fn credential_revocations(&self, id: &u64) -> SingleValueMapper<CredentialRevocation<Self::Api>>;
+
     #[view(getNextCredentialId)]
     #[storage_mapper("nextCredentialId")]
     fn next_credential_id(&self) -> SingleValueMapper<u64>;
@@ -28,17 +76,66 @@ pub trait StorageModule {
     #[storage_mapper("holderCredentials")]
     fn holder_credentials(&self, holder: &ManagedAddress) -> VecMapper<u64>;
 
+    #[storage_mapper("issuerCredentials")]
+    fn issuer_credentials(&self, issuer: &ManagedAddress) -> VecMapper<u64>;
+
+    // Position in `issuerCredentials` from which a deactivation cascade resumes
+    #[view(getCredentialRevocationCursor)]
+    #[storage_mapper("credentialRevocationCursor")]
+    fn credential_revocation_cursor(&self, issuer: &ManagedAddress) -> SingleValueMapper<usize>;
+
+    #[view(getStatusList)]
+    #[storage_mapper("statusLists")]
+    fn status_lists(&self, id: &u64) -> SingleValueMapper<StatusList<Self::Api>>;
+
+    #[storage_mapper("statusListBits")]
+    fn status_list_bits(&self, id: &u64) -> SingleValueMapper<ManagedBuffer>;
+
+    #[storage_mapper("nextStatusListId")]
+    fn next_status_list_id(&self) -> SingleValueMapper<u64>;
+
+    #[view(getCurrentStatusList)]
+    #[storage_mapper("issuerStatusList")]
+    fn issuer_status_list(&self, issuer: &ManagedAddress, purpose: &StatusPurpose) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("issuerStatusLists")]
+    fn issuer_status_lists(&self, issuer: &ManagedAddress) -> VecMapper<u64>;
+
+    #[storage_mapper("statusListCredential")]
+    fn status_list_credential(&self, list_id: &u64, index: &u64) -> SingleValueMapper<u64>;
+
+    #[view(getDelegation)]
     #[storage_mapper("delegations")]
     fn delegations(&self, id: &u64) -> SingleValueMapper<Delegation<Self::Api>>;
 
     #[storage_mapper("nextDelegationId")]
     fn next_delegation_id(&self) -> SingleValueMapper<u64>;
 
+    #[view(getMaxDelegationDepth)]
+    #[storage_mapper("maxDelegationDepth")]
+    fn max_delegation_depth(&self) -> SingleValueMapper<u32>;
+
     #[storage_mapper("delegateToDelegations")]
     fn delegate_to_delegations(&self, delegate: &ManagedAddress) -> VecMapper<u64>;
 
+    #[storage_mapper("delegatorDelegations")]
+    fn delegator_delegations(&self, delegator: &ManagedAddress) -> VecMapper<u64>;
+
+    // Position in `delegatorDelegations` from which a deactivation cascade resumes
+    #[view(getDelegationRevocationCursor)]
+    #[storage_mapper("delegationRevocationCursor")]
+    fn delegation_revocation_cursor(&self, delegator: &ManagedAddress) -> SingleValueMapper<usize>;
+
+    #[view(getIssuer)]
     #[storage_mapper("issuers")]
-    fn issuers(&self, address: &ManagedAddress) -> SingleValueMapper<bool>;
+    fn issuers(&self, address: &ManagedAddress) -> SingleValueMapper<IssuerProfile<Self::Api>>;
+
+    #[storage_mapper("issuerAddresses")]
+    fn issuer_addresses(&self) -> UnorderedSetMapper<ManagedAddress>;
+
+    #[view(getIssuerByDid)]
+    #[storage_mapper("issuerByDid")]
+    fn issuer_by_did(&self, did: &ManagedBuffer) -> SingleValueMapper<ManagedAddress>;
 
     #[storage_mapper("paused")]
     fn paused(&self) -> SingleValueMapper<bool>;
@@ -5,14 +5,107 @@ multiversx_sc::imports!();
 #[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
 pub struct DidDocument<M: ManagedTypeApi> {
     pub did: ManagedBuffer<M>,
-    pub controller: ManagedAddress<M>,
+    pub controllers: ManagedVec<M, ManagedAddress<M>>,
+    pub threshold: u32,
     pub verification_methods: ManagedVec<M, VerificationMethod<M>>,
     pub authentication: ManagedVec<M, ManagedBuffer<M>>,
     pub assertion_method: ManagedVec<M, ManagedBuffer<M>>,
     pub key_agreement: ManagedVec<M, ManagedBuffer<M>>,
     pub service_endpoints: ManagedVec<M, Service<M>>,
+    pub revoked_keys: ManagedVec<M, RevokedKey<M>>,
+    pub next_key_commitment: Option<ManagedBuffer<M>>,
     pub created: u64,
     pub updated: u64,
+    pub version_id: u64,
+    pub deactivated: bool,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
+pub enum DidAction<M: ManagedTypeApi> {
+    AddVerificationMethod(VerificationMethod<M>),
+    RemoveVerificationMethod(ManagedBuffer<M>),
+    CommitNextKey(ManagedBuffer<M>),
+    RotateKey(ManagedBuffer<M>, VerificationMethod<M>, ManagedBuffer<M>),
+    AddService(Service<M>),
+    RemoveService(ManagedBuffer<M>),
+    SetVerificationRelationships(
+        ManagedVec<M, ManagedBuffer<M>>,
+        ManagedVec<M, ManagedBuffer<M>>,
+        ManagedVec<M, ManagedBuffer<M>>,
+    ),
+    SetControllers(ManagedVec<M, ManagedAddress<M>>, u32),
+    SetRecoveryGuardians(
+        ManagedVec<M, ManagedAddress<M>>,
+        ManagedVec<M, ManagedBuffer<M>>,
+        u32,
+        u64,
+    ),
+    TransferControl(ManagedAddress<M>),
+    Deactivate(bool, bool),
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
+pub struct DidProposal<M: ManagedTypeApi> {
+    pub id: u64,
+    pub did: ManagedBuffer<M>,
+    pub proposer: ManagedAddress<M>,
+    pub action: DidAction<M>,
+    pub approvals: ManagedVec<M, ManagedAddress<M>>,
+    pub created: u64,
+    pub executed: bool,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
+pub struct RecoveryConfig<M: ManagedTypeApi> {
+    pub guardian_addresses: ManagedVec<M, ManagedAddress<M>>,
+    pub guardian_dids: ManagedVec<M, ManagedBuffer<M>>,
+    pub quorum: u32,
+    pub delay: u64,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
+pub struct RecoveryRequest<M: ManagedTypeApi> {
+    pub new_controller: ManagedAddress<M>,
+    pub initiator: ManagedAddress<M>,
+    pub approved_addresses: ManagedVec<M, ManagedAddress<M>>,
+    pub approved_dids: ManagedVec<M, ManagedBuffer<M>>,
+    // Controllers of guardian DIDs that approved; a guardian DID counts once its
+    // own threshold of controllers is among them
+    pub guardian_did_approvers: ManagedVec<M, ManagedAddress<M>>,
+    pub initiated: u64,
+    pub quorum_reached: Option<u64>,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
+pub struct DidDocumentVersion<M: ManagedTypeApi> {
+    pub version_id: u64,
+    pub timestamp: u64,
+    pub previous_hash: ManagedBuffer<M>,
+    pub document: DidDocument<M>,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
+pub struct DidDocumentMetadata {
+    pub created: u64,
+    pub updated: u64,
+    pub deactivated: bool,
+    pub version_id: u64,
+    pub next_update: Option<u64>,
+    pub next_version_id: Option<u64>,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
+pub struct DidResolutionMetadata<M: ManagedTypeApi> {
+    pub error: Option<ManagedBuffer<M>>,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
+pub struct DidResolutionResult<M: ManagedTypeApi> {
+    pub did_document: Option<DidDocument<M>>,
+    pub did_document_metadata: Option<DidDocumentMetadata>,
+    pub did_resolution_metadata: DidResolutionMetadata<M>,
+    pub dereferenced_method: Option<VerificationMethod<M>>,
+    pub dereferenced_service: Option<Service<M>>,
 }
 
 #[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, ManagedVecItem)]
@@ -23,6 +116,12 @@ pub struct VerificationMethod<M: ManagedTypeApi> {
     pub public_key_multibase: ManagedBuffer<M>,
 }
 
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, ManagedVecItem)]
+pub struct RevokedKey<M: ManagedTypeApi> {
+    pub method: VerificationMethod<M>,
+    pub revoked_at: u64,
+}
+
 #[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, ManagedVecItem)]
 pub struct Service<M: ManagedTypeApi> {
     pub id: ManagedBuffer<M>,
@@ -42,21 +141,66 @@ pub struct Credential<M: ManagedTypeApi> {
     pub issuance_date: u64,
     pub expiration_date: Option<u64>,
     pub revoked: bool,
+    pub suspension: Option<CredentialSuspension<M>>,
+    pub status: ManagedVec<M, CredentialStatusEntry>,
     pub delegation_id: Option<u64>,
     pub encryption_key: ManagedBuffer<M>,
     pub proof: CredentialProof<M>,
 }
 
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
+pub struct CredentialRevocation<M: ManagedTypeApi> {
+    pub reason: ManagedBuffer<M>,
+    pub revoked_at: u64,
+    pub revoked_by: ManagedAddress<M>,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
+pub struct CredentialSuspension<M: ManagedTypeApi> {
+    pub reason: ManagedBuffer<M>,
+    pub suspended_at: u64,
+    pub suspended_until: Option<u64>,
+    pub suspended_by: ManagedAddress<M>,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, Copy, PartialEq, ManagedVecItem)]
+pub enum StatusPurpose {
+    Revocation,
+    Suspension,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
+pub struct StatusList<M: ManagedTypeApi> {
+    pub id: u64,
+    pub issuer: ManagedAddress<M>,
+    pub purpose: StatusPurpose,
+    pub size: u64,
+    pub next_index: u64,
+    pub created: u64,
+    pub updated: u64,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, ManagedVecItem)]
+pub struct CredentialStatusEntry {
+    pub list_id: u64,
+    pub index: u64,
+    pub purpose: StatusPurpose,
+}
+
 #[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
 pub struct Claims<M: ManagedTypeApi> {
     pub data: ManagedVec<M, Claim<M>>,
     pub merkle_root: ManagedBuffer<M>,
+    // Every claim is stored as its salted hash only; the plaintext credential
+    // stays with the holder
+    pub hash_only: bool,
 }
 
 #[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, ManagedVecItem)]
 pub struct Claim<M: ManagedTypeApi> {
     pub attribute: ManagedBuffer<M>,
     pub value: ManagedBuffer<M>,
+    pub salt: ManagedBuffer<M>,
     pub hash: ManagedBuffer<M>,
 }
 
@@ -68,13 +212,48 @@ pub struct CredentialProof<M: ManagedTypeApi> {
     pub signature: ManagedBuffer<M>,
 }
 
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
+pub struct CredentialPayload<M: ManagedTypeApi> {
+    pub contract: ManagedAddress<M>,
+    pub issuer: ManagedAddress<M>,
+    pub holder: ManagedAddress<M>,
+    pub credential_type: ManagedVec<M, ManagedBuffer<M>>,
+    pub schema_id: ManagedBuffer<M>,
+    pub merkle_root: ManagedBuffer<M>,
+    pub expiration_date: Option<u64>,
+    pub delegation_id: Option<u64>,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
+pub struct VerificationResult<M: ManagedTypeApi> {
+    pub credential_id: u64,
+    pub valid: bool,
+    pub revoked: bool,
+    pub expired: bool,
+    pub suspended: bool,
+    pub issuer_active: bool,
+    pub schema_active: bool,
+    pub delegation_valid: bool,
+    pub proof_valid: bool,
+    pub delegation_chain: ManagedVec<M, u64>,
+    pub reasons: ManagedVec<M, ManagedBuffer<M>>,
+}
+
 #[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
 pub struct CredentialSchema<M: ManagedTypeApi> {
     pub id: ManagedBuffer<M>,
     pub type_: ManagedBuffer<M>,
     pub attributes: ManagedVec<M, SchemaAttribute<M>>,
     pub issuer: ManagedAddress<M>,
+    pub issuer_did: ManagedBuffer<M>,
     pub version: ManagedBuffer<M>,
+    // Strict schemas reject repeated claims
+    pub strict: bool,
+    // Unless set, claims for attributes the schema does not declare are rejected
+    pub additional_properties: bool,
+    pub created: u64,
+    pub deprecated_at: Option<u64>,
+    pub superseded_by: Option<ManagedBuffer<M>>,
 }
 
 #[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, ManagedVecItem)]
@@ -82,7 +261,24 @@ pub struct SchemaAttribute<M: ManagedTypeApi> {
     pub name: ManagedBuffer<M>,
     pub attribute_type: ManagedBuffer<M>,
     pub required: bool,
-    pub data_type: ManagedBuffer<M>
+    pub data_type: ManagedBuffer<M>,
+    pub constraints: AttributeConstraints<M>,
+}
+
+// Rules checked at issuance; zero and empty values mean no rule. For lists,
+// every rule except min/max items applies to each item.
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, ManagedVecItem)]
+pub struct AttributeConstraints<M: ManagedTypeApi> {
+    pub min_length: u32,
+    pub max_length: u32,
+    pub pattern: ManagedBuffer<M>,
+    pub allowed_values: ManagedVec<M, ManagedBuffer<M>>,
+    pub minimum: ManagedBuffer<M>,
+    pub maximum: ManagedBuffer<M>,
+    pub max_decimals: u32,
+    pub item_type: ManagedBuffer<M>,
+    pub min_items: u32,
+    pub max_items: u32,
 }
 
 #[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
@@ -93,4 +289,37 @@ pub struct Delegation<M: ManagedTypeApi> {
     pub credential_types: ManagedVec<M, ManagedBuffer<M>>,
     pub valid_until: u64,
     pub revoked: bool,
+    pub parent_id: Option<u64>,
+    pub depth: u32,
+    pub limits: DelegationLimits<M>,
+    pub issued_count: u64,
+    pub created: u64,
+    pub updated: u64,
+}
+
+// Empty lists place no restriction on schemas or holders
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
+pub struct DelegationLimits<M: ManagedTypeApi> {
+    pub max_credentials: Option<u64>,
+    pub allowed_schema_ids: ManagedVec<M, ManagedBuffer<M>>,
+    pub allowed_holders: ManagedVec<M, ManagedAddress<M>>,
+    pub max_sub_delegation_depth: u32,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, Copy, PartialEq)]
+pub enum IssuerStatus {
+    Active,
+    Suspended,
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, Clone)]
+pub struct IssuerProfile<M: ManagedTypeApi> {
+    pub address: ManagedAddress<M>,
+    pub name: ManagedBuffer<M>,
+    pub did: ManagedBuffer<M>,
+    pub website: ManagedBuffer<M>,
+    pub accreditation: ManagedBuffer<M>,
+    pub status: IssuerStatus,
+    pub registered: u64,
+    pub updated: u64,
 }
\ No newline at end of file
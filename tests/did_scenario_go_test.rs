@@ -0,0 +1,11 @@
+use multiversx_sc_scenario::*;
+
+fn world() -> ScenarioWorld {
+    ScenarioWorld::vm_go()
+}
+
+#[test]
+#[ignore = "requires the mx-scenario-go VM and a built output/did.mxsc.json"]
+fn deploy_go() {
+    world().run("scenarios/deploy.scen.json");
+}
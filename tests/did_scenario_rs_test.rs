@@ -0,0 +1,13 @@
+use multiversx_sc_scenario::*;
+
+fn world() -> ScenarioWorld {
+    let mut blockchain = ScenarioWorld::new();
+
+    blockchain.register_contract("mxsc:output/did.mxsc.json", did::ContractBuilder);
+    blockchain
+}
+
+#[test]
+fn deploy_rs() {
+    world().run("scenarios/deploy.scen.json");
+}
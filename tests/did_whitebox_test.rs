@@ -0,0 +1,975 @@
+use did::{storage::StorageModule, structures::*, DigitalIdentityContract};
+use ed25519_dalek::{Signer, SigningKey};
+use multiversx_sc_scenario::imports::*;
+
+const CODE_PATH: MxscPath = MxscPath::new("output/did.mxsc.json");
+const DID_ADDRESS: TestSCAddress = TestSCAddress::new("did");
+const OWNER: TestAddress = TestAddress::new("owner");
+const ALICE: TestAddress = TestAddress::new("alice");
+const BOB: TestAddress = TestAddress::new("bob");
+const CAROL: TestAddress = TestAddress::new("carol");
+const DAVE: TestAddress = TestAddress::new("dave");
+
+fn world() -> ScenarioWorld {
+    let mut world = ScenarioWorld::new();
+    world.register_contract(CODE_PATH, did::ContractBuilder);
+    world.account(OWNER).nonce(1);
+    for account in [ALICE, BOB, CAROL, DAVE] {
+        world.account(account).nonce(1);
+    }
+    world
+        .tx()
+        .from(OWNER)
+        .raw_deploy()
+        .code(CODE_PATH)
+        .new_address(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| sc.init());
+    world
+}
+
+fn key_hash(public_key_multibase: &[u8]) -> ManagedBuffer<DebugApi> {
+    let sc = did::contract_obj::<DebugApi>();
+    sc.crypto()
+        .sha256(ManagedBuffer::new_from_bytes(public_key_multibase))
+        .as_managed_buffer()
+        .clone()
+}
+
+// Every test account signs with an Ed25519 key derived from its address
+fn signing_key(address: TestAddress) -> SigningKey {
+    SigningKey::from_bytes(&address.eval_to_array())
+}
+
+// Base16 multibase of the account's multicodec-prefixed public key
+fn public_key_multibase(address: TestAddress) -> Vec<u8> {
+    let mut encoded = String::from("fed01");
+    for byte in signing_key(address).verifying_key().as_bytes() {
+        encoded.push_str(&format!("{byte:02x}"));
+    }
+    encoded.into_bytes()
+}
+
+fn method(id: &[u8], controller: TestAddress, public_key_multibase: &[u8]) -> VerificationMethod<DebugApi> {
+    VerificationMethod {
+        id: ManagedBuffer::new_from_bytes(id),
+        type_: ManagedBuffer::from(b"Ed25519VerificationKey2020"),
+        controller: controller.to_managed_address(),
+        public_key_multibase: ManagedBuffer::new_from_bytes(public_key_multibase),
+    }
+}
+
+// Creates a DID controlled by `controller` and returns it
+fn create_did(world: &mut ScenarioWorld, controller: TestAddress, next_key: Option<&[u8]>) -> Vec<u8> {
+    world
+        .tx()
+        .from(controller)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| {
+            let commitment = next_key.map(key_hash);
+            sc.create_did(
+                method(b"key-1", controller, &public_key_multibase(controller)),
+                ManagedVec::new(),
+                None,
+                commitment.into(),
+            );
+        });
+
+    let mut did = Vec::new();
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        let address = controller.to_managed_address();
+        did = sc.address_to_did(&address).get().to_boxed_bytes().into_vec();
+    });
+    did
+}
+
+fn buffer(bytes: &[u8]) -> ManagedBuffer<DebugApi> {
+    ManagedBuffer::new_from_bytes(bytes)
+}
+
+#[test]
+fn rotate_key_with_pre_commitment() {
+    let mut world = world();
+    let did = create_did(&mut world, ALICE, Some(b"z6MkKey2"));
+
+    world
+        .tx()
+        .from(ALICE)
+        .to(DID_ADDRESS)
+        .returns(ExpectError(4, "New key does not match the pre-rotation commitment"))
+        .whitebox(did::contract_obj, |sc| {
+            sc.rotate_key(
+                buffer(&did),
+                buffer(b"key-1"),
+                method(b"key-2", ALICE, b"z6MkOther"),
+                key_hash(b"z6MkKey3"),
+            );
+        });
+
+    world
+        .tx()
+        .from(ALICE)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| {
+            sc.rotate_key(
+                buffer(&did),
+                buffer(b"key-1"),
+                method(b"key-2", ALICE, b"z6MkKey2"),
+                key_hash(b"z6MkKey3"),
+            );
+        });
+
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        let document = sc.did_documents(&buffer(&did)).get();
+        assert_eq!(document.version_id, 2);
+        assert_eq!(document.authentication.len(), 1);
+        assert_eq!(*document.authentication.get(0), buffer(b"key-2"));
+        assert_eq!(*document.assertion_method.get(0), buffer(b"key-2"));
+        assert_eq!(document.verification_methods.len(), 1);
+        assert_eq!(document.revoked_keys.len(), 1);
+        assert_eq!(document.revoked_keys.get(0).method.id, buffer(b"key-1"));
+        assert_eq!(document.next_key_commitment, Some(key_hash(b"z6MkKey3")));
+        assert_eq!(sc.get_did_document_version_count(buffer(&did)), 2);
+    });
+}
+
+#[test]
+fn rotate_key_requires_a_commitment() {
+    let mut world = world();
+    let did = create_did(&mut world, ALICE, None);
+
+    world
+        .tx()
+        .from(ALICE)
+        .to(DID_ADDRESS)
+        .returns(ExpectError(4, "No next key commitment"))
+        .whitebox(did::contract_obj, |sc| {
+            sc.rotate_key(
+                buffer(&did),
+                buffer(b"key-1"),
+                method(b"key-2", ALICE, b"z6MkKey2"),
+                key_hash(b"z6MkKey3"),
+            );
+        });
+
+    world
+        .tx()
+        .from(ALICE)
+        .to(DID_ADDRESS)
+        .returns(ExpectError(4, "Key commitment must be a SHA-256 hash"))
+        .whitebox(did::contract_obj, |sc| {
+            sc.commit_next_key(buffer(&did), buffer(b"z6MkKey2"));
+        });
+
+    world
+        .tx()
+        .from(ALICE)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| {
+            sc.commit_next_key(buffer(&did), key_hash(b"z6MkKey2"));
+            sc.rotate_key(
+                buffer(&did),
+                buffer(b"key-1"),
+                method(b"key-2", ALICE, b"z6MkKey2"),
+                key_hash(b"z6MkKey3"),
+            );
+        });
+
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        let document = sc.did_documents(&buffer(&did)).get();
+        assert_eq!(*document.authentication.get(0), buffer(b"key-2"));
+        assert_eq!(document.version_id, 3);
+    });
+}
+
+fn service(id: &[u8]) -> Service<DebugApi> {
+    Service {
+        id: ManagedBuffer::new_from_bytes(id),
+        type_: ManagedBuffer::from(b"LinkedDomains"),
+        endpoint: ManagedBuffer::from(b"https://example.com"),
+    }
+}
+
+// Makes Alice, Bob and Carol the controllers of Alice's DID with a 2-of-3 threshold
+fn create_shared_did(world: &mut ScenarioWorld) -> Vec<u8> {
+    let did = create_did(world, ALICE, None);
+    world
+        .tx()
+        .from(ALICE)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| {
+            let mut controllers = ManagedVec::new();
+            for controller in [ALICE, BOB, CAROL] {
+                controllers.push(controller.to_managed_address());
+            }
+            sc.set_did_controllers_endpoint(buffer(&did), controllers, 2);
+        });
+    did
+}
+
+fn propose(world: &mut ScenarioWorld, proposer: TestAddress, did: &[u8], action: fn() -> DidAction<DebugApi>) -> u64 {
+    let mut proposal_id = 0;
+    world
+        .tx()
+        .from(proposer)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| {
+            proposal_id = sc.propose_did_update(buffer(did), action());
+        });
+    proposal_id
+}
+
+#[test]
+fn threshold_proposals_execute_once_approved() {
+    let mut world = world();
+    let did = create_shared_did(&mut world);
+
+    world
+        .tx()
+        .from(ALICE)
+        .to(DID_ADDRESS)
+        .returns(ExpectError(4, "DID updates require threshold approval"))
+        .whitebox(did::contract_obj, |sc| {
+            sc.add_service(buffer(&did), service(b"website"));
+        });
+    world
+        .tx()
+        .from(DAVE)
+        .to(DID_ADDRESS)
+        .returns(ExpectError(4, "Only a DID controller can update the document"))
+        .whitebox(did::contract_obj, |sc| {
+            sc.propose_did_update(buffer(&did), DidAction::AddService(service(b"website")));
+        });
+
+    let proposal_id = propose(&mut world, ALICE, &did, || DidAction::AddService(service(b"website")));
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        assert!(!sc.did_proposals(&proposal_id).get().executed);
+        assert!(sc.did_documents(&buffer(&did)).get().service_endpoints.is_empty());
+    });
+
+    world
+        .tx()
+        .from(ALICE)
+        .to(DID_ADDRESS)
+        .returns(ExpectError(4, "Proposal already approved"))
+        .whitebox(did::contract_obj, |sc| sc.approve_did_update(proposal_id));
+    world
+        .tx()
+        .from(BOB)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| sc.approve_did_update(proposal_id));
+    world
+        .tx()
+        .from(CAROL)
+        .to(DID_ADDRESS)
+        .returns(ExpectError(4, "Proposal already executed"))
+        .whitebox(did::contract_obj, |sc| sc.approve_did_update(proposal_id));
+
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        assert!(sc.did_proposals(&proposal_id).get().executed);
+        let document = sc.did_documents(&buffer(&did)).get();
+        assert_eq!(document.service_endpoints.len(), 1);
+        assert_eq!(document.service_endpoints.get(0).id, buffer(b"website"));
+        assert_eq!(document.version_id, 3);
+    });
+}
+
+#[test]
+fn proposals_only_count_current_controllers() {
+    let mut world = world();
+    let did = create_shared_did(&mut world);
+
+    // Carol is dropped before Bob approves, so her earlier approval no longer counts
+    let service_proposal = propose(&mut world, CAROL, &did, || DidAction::AddService(service(b"website")));
+    let controllers_proposal = propose(&mut world, ALICE, &did, || {
+        let mut controllers = ManagedVec::new();
+        controllers.push(ALICE.to_managed_address());
+        controllers.push(BOB.to_managed_address());
+        DidAction::SetControllers(controllers, 2)
+    });
+    world
+        .tx()
+        .from(BOB)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| sc.approve_did_update(controllers_proposal));
+    world
+        .tx()
+        .from(ALICE)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| sc.approve_did_update(service_proposal));
+
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        let document = sc.did_documents(&buffer(&did)).get();
+        assert_eq!(document.controllers.len(), 2);
+        assert!(document.service_endpoints.is_empty());
+        assert!(!sc.did_proposals(&service_proposal).get().executed);
+        let carol = CAROL.to_managed_address();
+        assert!(sc.did_by_controller(&carol).is_empty());
+    });
+}
+
+#[test]
+fn transfer_proposals_leave_the_document_version_alone() {
+    let mut world = world();
+    let did = create_shared_did(&mut world);
+
+    let proposal_id = propose(&mut world, ALICE, &did, || DidAction::TransferControl(DAVE.to_managed_address()));
+    world
+        .tx()
+        .from(CAROL)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| sc.approve_did_update(proposal_id));
+
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        assert_eq!(sc.pending_did_transfer(&buffer(&did)).get(), DAVE.to_managed_address());
+        assert_eq!(sc.did_documents(&buffer(&did)).get().version_id, 2);
+        assert_eq!(sc.get_did_document_version_count(buffer(&did)), 2);
+    });
+
+    world
+        .tx()
+        .from(DAVE)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| sc.accept_did_control(buffer(&did)));
+
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        let document = sc.did_documents(&buffer(&did)).get();
+        assert_eq!(document.controllers.len(), 1);
+        assert_eq!(*document.controllers.get(0), DAVE.to_managed_address());
+        assert_eq!(document.threshold, 1);
+        assert_eq!(document.version_id, 3);
+        assert_eq!(sc.address_to_did(&DAVE.to_managed_address()).get(), buffer(&did));
+        assert!(sc.address_to_did(&ALICE.to_managed_address()).is_empty());
+    });
+}
+
+// Bob and Carol's DID guard Alice's DID; both are needed and the recovery
+// waits 100 seconds once they agree
+fn create_guarded_did(world: &mut ScenarioWorld) -> Vec<u8> {
+    let did = create_did(world, ALICE, None);
+    let guardian_did = create_did(world, CAROL, None);
+    world
+        .tx()
+        .from(ALICE)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| {
+            sc.set_recovery_guardians(
+                buffer(&did),
+                ManagedVec::from_single_item(BOB.to_managed_address()),
+                ManagedVec::from_single_item(buffer(&guardian_did)),
+                2,
+                100,
+            );
+        });
+    did
+}
+
+#[test]
+fn guardians_recover_control_after_the_delay() {
+    let mut world = world();
+    world.current_block().block_timestamp(1_000);
+    let did = create_guarded_did(&mut world);
+
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        assert_eq!(sc.recovery_config(&buffer(&did)).get().quorum, 2);
+        assert_eq!(sc.did_documents(&buffer(&did)).get().version_id, 1);
+    });
+
+    world
+        .tx()
+        .from(DAVE)
+        .to(DID_ADDRESS)
+        .returns(ExpectError(4, "Only a guardian can approve a recovery"))
+        .whitebox(did::contract_obj, |sc| {
+            sc.initiate_recovery(buffer(&did), DAVE.to_managed_address());
+        });
+    world
+        .tx()
+        .from(BOB)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| {
+            sc.initiate_recovery(buffer(&did), DAVE.to_managed_address());
+        });
+    world
+        .tx()
+        .from(BOB)
+        .to(DID_ADDRESS)
+        .returns(ExpectError(4, "Recovery quorum not reached"))
+        .whitebox(did::contract_obj, |sc| sc.execute_recovery(buffer(&did)));
+
+    world.current_block().block_timestamp(1_050);
+    world
+        .tx()
+        .from(CAROL)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| sc.approve_recovery(buffer(&did)));
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        let request = sc.recovery_request(&buffer(&did)).get();
+        assert_eq!(request.approved_dids.len(), 1);
+        assert_eq!(request.quorum_reached, Some(1_050));
+    });
+
+    world.current_block().block_timestamp(1_149);
+    world
+        .tx()
+        .from(BOB)
+        .to(DID_ADDRESS)
+        .returns(ExpectError(4, "Recovery waiting period has not ended"))
+        .whitebox(did::contract_obj, |sc| sc.execute_recovery(buffer(&did)));
+
+    world.current_block().block_timestamp(1_150);
+    world
+        .tx()
+        .from(BOB)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| sc.execute_recovery(buffer(&did)));
+
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        let document = sc.did_documents(&buffer(&did)).get();
+        assert_eq!(document.controllers.len(), 1);
+        assert_eq!(*document.controllers.get(0), DAVE.to_managed_address());
+        assert_eq!(document.updated, 1_150);
+        assert!(sc.recovery_request(&buffer(&did)).is_empty());
+        assert!(sc.did_by_controller(&ALICE.to_managed_address()).is_empty());
+    });
+}
+
+#[test]
+fn controllers_can_cancel_a_recovery() {
+    let mut world = world();
+    let did = create_guarded_did(&mut world);
+
+    world
+        .tx()
+        .from(BOB)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| {
+            sc.initiate_recovery(buffer(&did), DAVE.to_managed_address());
+        });
+    world
+        .tx()
+        .from(CAROL)
+        .to(DID_ADDRESS)
+        .returns(ExpectError(4, "Only a DID controller can update the document"))
+        .whitebox(did::contract_obj, |sc| sc.cancel_recovery(buffer(&did)));
+    world
+        .tx()
+        .from(ALICE)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| sc.cancel_recovery(buffer(&did)));
+    world
+        .tx()
+        .from(CAROL)
+        .to(DID_ADDRESS)
+        .returns(ExpectError(4, "No recovery in progress"))
+        .whitebox(did::contract_obj, |sc| sc.approve_recovery(buffer(&did)));
+
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        let document = sc.did_documents(&buffer(&did)).get();
+        assert_eq!(*document.controllers.get(0), ALICE.to_managed_address());
+    });
+}
+
+#[test]
+fn losing_control_points_the_address_at_a_remaining_did() {
+    let mut world = world();
+    let first = create_did(&mut world, ALICE, None);
+    world
+        .tx()
+        .from(ALICE)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| {
+            sc.create_did(method(b"key-1", ALICE, b"z6MkKey1"), ManagedVec::new(), None, None.into());
+        });
+
+    let mut second = Vec::new();
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        let alice = ALICE.to_managed_address();
+        assert_eq!(sc.address_to_did(&alice).get(), buffer(&first));
+        for did in sc.did_by_controller(&alice).iter() {
+            if did != buffer(&first) {
+                second = did.to_boxed_bytes().into_vec();
+            }
+        }
+    });
+    assert!(!second.is_empty());
+
+    world
+        .tx()
+        .from(ALICE)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| {
+            sc.transfer_did_control(buffer(&first), BOB.to_managed_address());
+        });
+    world
+        .tx()
+        .from(BOB)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| sc.accept_did_control(buffer(&first)));
+
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        assert_eq!(sc.address_to_did(&ALICE.to_managed_address()).get(), buffer(&second));
+        assert_eq!(sc.address_to_did(&BOB.to_managed_address()).get(), buffer(&first));
+    });
+}
+
+const CREDENTIAL_TYPE: &[u8] = b"MembershipCredential";
+
+fn credential_types() -> ManagedVec<DebugApi, ManagedBuffer<DebugApi>> {
+    ManagedVec::from_single_item(buffer(CREDENTIAL_TYPE))
+}
+
+// Registers `issuer` with a fresh DID and a schema holding one private claim,
+// and returns the schema id
+fn register_issuer(world: &mut ScenarioWorld, issuer: TestAddress) -> Vec<u8> {
+    let did = create_did(world, issuer, None);
+    world
+        .tx()
+        .from(OWNER)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| {
+            sc.register_issuer(
+                issuer.to_managed_address(),
+                buffer(b"Issuer"),
+                buffer(&did),
+                buffer(b"https://example.com"),
+                buffer(b"accredited"),
+            );
+        });
+
+    world
+        .tx()
+        .from(issuer)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| {
+            let attribute = SchemaAttribute {
+                name: buffer(b"memberId"),
+                attribute_type: buffer(b"private"),
+                required: true,
+                data_type: buffer(b"string"),
+                constraints: AttributeConstraints {
+                    min_length: 0,
+                    max_length: 0,
+                    pattern: ManagedBuffer::new(),
+                    allowed_values: ManagedVec::new(),
+                    minimum: ManagedBuffer::new(),
+                    maximum: ManagedBuffer::new(),
+                    max_decimals: 0,
+                    item_type: ManagedBuffer::new(),
+                    min_items: 0,
+                    max_items: 0,
+                },
+            };
+            sc.register_schema(
+                buffer(CREDENTIAL_TYPE),
+                ManagedVec::from_single_item(attribute),
+                buffer(b"1"),
+                true,
+                false,
+            );
+        });
+
+    let mut schema_id = Vec::new();
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        schema_id = sc
+            .issuer_schemas(&issuer.to_managed_address())
+            .get(1)
+            .to_boxed_bytes()
+            .into_vec();
+    });
+    schema_id
+}
+
+// Issues a credential from `signer`, on behalf of `issuer` under a
+// delegation, and returns its id
+fn issue_credential(
+    world: &mut ScenarioWorld,
+    issuer: TestAddress,
+    signer: TestAddress,
+    holder: TestAddress,
+    schema_id: &[u8],
+    delegation_id: Option<u64>,
+    expected_error: Option<&str>,
+) -> u64 {
+    let tx = world.tx().from(signer).to(DID_ADDRESS);
+    let body = |sc: did::ContractObj<DebugApi>| {
+        // Only a private claim, so the Merkle tree has no leaves
+        let merkle_root = key_hash(b"");
+        let payload = CredentialPayload {
+            contract: DID_ADDRESS.to_managed_address(),
+            issuer: issuer.to_managed_address(),
+            holder: holder.to_managed_address(),
+            credential_type: credential_types(),
+            schema_id: buffer(schema_id),
+            merkle_root,
+            expiration_date: None,
+            delegation_id,
+        };
+        let encoded = sc.serializer().top_encode_to_managed_buffer(&payload);
+        let message = sc.crypto().sha256(&encoded);
+        let signature = signing_key(signer).sign(message.to_byte_array().as_slice());
+
+        let claim = Claim {
+            attribute: buffer(b"memberId"),
+            value: ManagedBuffer::new(),
+            salt: ManagedBuffer::new(),
+            hash: key_hash(b"member id commitment"),
+        };
+        sc.issue_credential(
+            holder.to_managed_address(),
+            credential_types(),
+            buffer(schema_id),
+            Claims {
+                data: ManagedVec::from_single_item(claim),
+                merkle_root: ManagedBuffer::new(),
+                hash_only: false,
+            },
+            None,
+            ManagedBuffer::new(),
+            buffer(b"key-1"),
+            buffer(&signature.to_bytes()),
+            delegation_id,
+        );
+    };
+    match expected_error {
+        Some(message) => {
+            tx.returns(ExpectError(4, message)).whitebox(did::contract_obj, body);
+            return 0;
+        }
+        None => tx.whitebox(did::contract_obj, body),
+    }
+
+    let mut credential_id = 0;
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        let credentials = sc.holder_credentials(&holder.to_managed_address());
+        credential_id = credentials.get(credentials.len());
+    });
+    credential_id
+}
+
+fn limits(
+    max_credentials: Option<u64>,
+    allowed_holders: &[TestAddress],
+    max_sub_delegation_depth: u32,
+) -> DelegationLimits<DebugApi> {
+    let mut holders = ManagedVec::new();
+    for holder in allowed_holders {
+        holders.push(holder.to_managed_address());
+    }
+    DelegationLimits {
+        max_credentials,
+        allowed_schema_ids: ManagedVec::new(),
+        allowed_holders: holders,
+        max_sub_delegation_depth,
+    }
+}
+
+fn sub_delegate(
+    world: &mut ScenarioWorld,
+    delegator: TestAddress,
+    parent_id: u64,
+    delegate: TestAddress,
+    valid_until: u64,
+    limits: fn() -> DelegationLimits<DebugApi>,
+    expected_error: Option<&str>,
+) -> u64 {
+    let mut delegation_id = 0;
+    let tx = world.tx().from(delegator).to(DID_ADDRESS);
+    let body = |sc: did::ContractObj<DebugApi>| {
+        delegation_id = sc.sub_delegate(
+            parent_id,
+            delegate.to_managed_address(),
+            credential_types(),
+            valid_until,
+            limits(),
+        );
+    };
+    match expected_error {
+        Some(message) => tx.returns(ExpectError(4, message)).whitebox(did::contract_obj, body),
+        None => tx.whitebox(did::contract_obj, body),
+    }
+    delegation_id
+}
+
+#[test]
+fn delegation_chains_are_bounded_by_depth_and_scope() {
+    let mut world = world();
+    world.current_block().block_timestamp(1_000);
+    let schema_id = register_issuer(&mut world, ALICE);
+    create_did(&mut world, DAVE, None);
+
+    world
+        .tx()
+        .from(BOB)
+        .to(DID_ADDRESS)
+        .returns(ExpectError(4, "Only admin can perform this action"))
+        .whitebox(did::contract_obj, |sc| sc.set_max_delegation_depth(2));
+    world
+        .tx()
+        .from(OWNER)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| sc.set_max_delegation_depth(2));
+
+    world
+        .tx()
+        .from(ALICE)
+        .to(DID_ADDRESS)
+        .returns(ExpectError(4, "Delegation chain would exceed the maximum depth"))
+        .whitebox(did::contract_obj, |sc| {
+            sc.delegate_credential_issuance(
+                BOB.to_managed_address(),
+                credential_types(),
+                10_000,
+                OptionalValue::Some(limits(None, &[], 3)),
+            );
+        });
+    let mut root_id = 0;
+    world
+        .tx()
+        .from(ALICE)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| {
+            root_id = sc.delegate_credential_issuance(
+                BOB.to_managed_address(),
+                credential_types(),
+                10_000,
+                OptionalValue::Some(limits(None, &[], 2)),
+            );
+        });
+
+    // Sub-delegations can only narrow their parent
+    world
+        .tx()
+        .from(BOB)
+        .to(DID_ADDRESS)
+        .returns(ExpectError(
+            4,
+            "Delegation 0 only permits issuance of credential type: MembershipCredential",
+        ))
+        .whitebox(did::contract_obj, |sc| {
+            sc.sub_delegate(
+                root_id,
+                CAROL.to_managed_address(),
+                ManagedVec::from_single_item(buffer(b"OtherCredential")),
+                5_000,
+                limits(None, &[], 1),
+            );
+        });
+    sub_delegate(
+        &mut world, BOB, root_id, CAROL, 20_000, || limits(None, &[], 1),
+        Some("Sub-delegation cannot outlive the parent delegation"),
+    );
+    let middle_id = sub_delegate(
+        &mut world, BOB, root_id, CAROL, 5_000, || limits(Some(1), &[BOB], 1),
+        None,
+    );
+
+    sub_delegate(
+        &mut world, CAROL, middle_id, DAVE, 5_000, || limits(Some(1), &[BOB], 1),
+        Some("Delegation chain would exceed the maximum depth"),
+    );
+    sub_delegate(
+        &mut world, CAROL, middle_id, DAVE, 5_000, || limits(None, &[BOB], 0),
+        Some("Credential limit cannot exceed the parent delegation"),
+    );
+    sub_delegate(
+        &mut world, CAROL, middle_id, DAVE, 5_000, || limits(Some(1), &[], 0),
+        Some("Allowed holders must be a subset of the parent delegation"),
+    );
+    let leaf_id = sub_delegate(
+        &mut world, CAROL, middle_id, DAVE, 5_000, || limits(Some(1), &[BOB], 0),
+        None,
+    );
+    sub_delegate(
+        &mut world, DAVE, leaf_id, ALICE, 5_000, || limits(Some(1), &[BOB], 0),
+        Some("Delegation chain would exceed the maximum depth"),
+    );
+    let closed_id = sub_delegate(
+        &mut world, BOB, root_id, DAVE, 5_000, || limits(None, &[], 0),
+        None,
+    );
+    sub_delegate(
+        &mut world, DAVE, closed_id, CAROL, 5_000, || limits(None, &[], 0),
+        Some("Parent delegation does not allow further sub-delegation"),
+    );
+
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        let chain: Vec<u64> = sc.get_delegation_chain(leaf_id).into_iter().collect();
+        assert_eq!(chain, [root_id, middle_id, leaf_id].to_vec());
+        assert_eq!(sc.delegations(&leaf_id).get().depth, 2);
+    });
+
+    // Limits apply at every level of the chain
+    issue_credential(
+        &mut world, ALICE, DAVE, CAROL, &schema_id, Some(leaf_id),
+        Some("Holder not allowed under this delegation"),
+    );
+    let credential_id = issue_credential(
+        &mut world, ALICE, DAVE, BOB, &schema_id, Some(leaf_id),
+        None,
+    );
+    issue_credential(
+        &mut world, ALICE, DAVE, BOB, &schema_id, Some(leaf_id),
+        Some("Delegation credential limit reached"),
+    );
+
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        let credential = sc.credentials(&credential_id).get();
+        assert_eq!(credential.issuer, ALICE.to_managed_address());
+        assert_eq!(credential.delegation_id, Some(leaf_id));
+        assert_eq!(sc.delegations(&middle_id).get().issued_count, 1);
+
+        let result = sc.verify_credential_view(credential_id);
+        assert!(result.valid);
+        assert!(result.proof_valid);
+        assert_eq!(result.delegation_chain.len(), 3);
+    });
+
+    // Revoking the root invalidates every link below it
+    world
+        .tx()
+        .from(ALICE)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| sc.revoke_delegation(root_id));
+    issue_credential(
+        &mut world, ALICE, DAVE, BOB, &schema_id, Some(leaf_id),
+        Some(&format!("Delegation {root_id} is revoked")),
+    );
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        let result = sc.verify_credential_view(credential_id);
+        assert!(!result.valid);
+        assert!(!result.delegation_valid);
+    });
+}
+
+fn allocate_status_list(world: &mut ScenarioWorld, issuer: TestAddress, purpose: StatusPurpose) -> u64 {
+    let mut list_id = 0;
+    world
+        .tx()
+        .from(issuer)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| list_id = sc.allocate_status_list(purpose));
+    list_id
+}
+
+fn update_status_bits(
+    world: &mut ScenarioWorld,
+    issuer: TestAddress,
+    list_id: u64,
+    updates: &[(u64, bool)],
+    expected_error: Option<&str>,
+) {
+    let tx = world.tx().from(issuer).to(DID_ADDRESS);
+    let body = |sc: did::ContractObj<DebugApi>| {
+        let mut encoded = MultiValueEncoded::new();
+        for (index, value) in updates {
+            encoded.push(MultiValue2::from((*index, *value)));
+        }
+        sc.update_status_list_bits(list_id, encoded);
+    };
+    match expected_error {
+        Some(message) => tx.returns(ExpectError(4, message)).whitebox(did::contract_obj, body),
+        None => tx.whitebox(did::contract_obj, body),
+    }
+}
+
+// Decodes a multibase base64url `encodedList` and inflates its gzip member
+fn decode_status_list(encoded: &[u8]) -> Vec<u8> {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    assert_eq!(encoded[0], b'u');
+
+    let mut gzip = Vec::new();
+    let (mut accumulator, mut bits) = (0u32, 0);
+    for &c in &encoded[1..] {
+        let digit = ALPHABET.iter().position(|&a| a == c).unwrap() as u32;
+        accumulator = (accumulator << 6) | digit;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            gzip.push((accumulator >> bits) as u8);
+        }
+    }
+
+    let deflated = &gzip[10..gzip.len() - 8];
+    miniz_oxide::inflate::decompress_to_vec(deflated).unwrap()
+}
+
+#[test]
+fn status_lists_track_revocation_and_suspension() {
+    let mut world = world();
+    let schema_id = register_issuer(&mut world, ALICE);
+
+    world
+        .tx()
+        .from(BOB)
+        .to(DID_ADDRESS)
+        .returns(ExpectError(4, "Only registered issuers can allocate status lists"))
+        .whitebox(did::contract_obj, |sc| {
+            sc.allocate_status_list(StatusPurpose::Revocation);
+        });
+    let revocation_list = allocate_status_list(&mut world, ALICE, StatusPurpose::Revocation);
+    let suspension_list = allocate_status_list(&mut world, ALICE, StatusPurpose::Suspension);
+
+    let first = issue_credential(&mut world, ALICE, ALICE, BOB, &schema_id, None, None);
+    let second = issue_credential(&mut world, ALICE, ALICE, CAROL, &schema_id, None, None);
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        let status = sc.credentials(&second).get().status;
+        assert_eq!(status.len(), 2);
+        assert_eq!(status.get(0).list_id, revocation_list);
+        assert_eq!(status.get(0).index, 1);
+        assert_eq!(status.get(1).list_id, suspension_list);
+        assert_eq!(status.get(1).index, 1);
+        assert_eq!(sc.status_lists(&revocation_list).get().size, 131_072);
+    });
+
+    // Endpoints and list updates keep credentials and bits in sync
+    world
+        .tx()
+        .from(ALICE)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| {
+            sc.revoke_credential_endpoint(first, buffer(b"compromised"));
+            sc.suspend_credential(second, buffer(b"under review"), OptionalValue::None);
+        });
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        assert!(sc.get_status(revocation_list, 0));
+        assert!(!sc.get_status(revocation_list, 1));
+        assert!(!sc.get_status(suspension_list, 0));
+        assert!(sc.get_status(suspension_list, 1));
+        assert!(sc.verify_credential_view(first).revoked);
+        assert!(sc.verify_credential_view(second).suspended);
+    });
+
+    world
+        .tx()
+        .from(ALICE)
+        .to(DID_ADDRESS)
+        .whitebox(did::contract_obj, |sc| sc.reinstate_credential(second));
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        assert!(!sc.get_status(suspension_list, 1));
+        assert!(sc.verify_credential_view(second).valid);
+    });
+
+    update_status_bits(&mut world, ALICE, suspension_list, &[(1, true)], None);
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        assert!(sc.credentials(&second).get().suspension.is_some());
+    });
+    update_status_bits(&mut world, ALICE, suspension_list, &[(1, false)], None);
+    update_status_bits(&mut world, ALICE, revocation_list, &[(1, true)], None);
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        let credential = sc.credentials(&second).get();
+        assert!(credential.suspension.is_none());
+        assert!(credential.revoked);
+    });
+
+    update_status_bits(&mut world, ALICE, revocation_list, &[(1, false)], Some("Revocation cannot be undone"));
+    update_status_bits(&mut world, ALICE, revocation_list, &[(2, true)], Some("Status index not assigned"));
+    update_status_bits(&mut world, BOB, revocation_list, &[(0, true)], Some("Only the list issuer can update it"));
+
+    world.query().to(DID_ADDRESS).whitebox(did::contract_obj, |sc| {
+        let encoded = sc.get_encoded_status_list(revocation_list).to_boxed_bytes();
+        let bits = decode_status_list(encoded.as_slice());
+        assert_eq!(bits.len(), 131_072 / 8);
+        assert_eq!(bits[0], 0b1100_0000);
+        assert!(bits[1..].iter().all(|&byte| byte == 0));
+    });
+}